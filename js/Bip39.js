@@ -1,18 +1,92 @@
-import bip39 from 'bip39';
 import { Buffer } from 'safe-buffer';
 import RustModule from './RustModule';
-import Blake2b from './Blake2b';
 import { apply } from './utils/functions';
+import { callSecretResult } from './utils/jrpc';
 
-export const mnemonicToEntropy = (mnemonic) => (
-  Buffer.from(bip39.mnemonicToEntropy(mnemonic), 'hex')
+// the official BIP39 word lists
+export const LANGUAGES = [
+  'english',
+  'french',
+  'japanese',
+  'korean',
+  'chinese_simplified',
+  'chinese_traditional',
+  'italian',
+  'spanish',
+];
+
+/**
+ * Get the entropy of the given mnemonic phrase, checking the words
+ * and the checksum.
+ *
+ * @param module   - the WASM module that is used for crypto operations
+ * @param mnemonic - the mnemonic phrase (string)
+ * @param language - the language of the word list (default 'english')
+ * @returns {*}    - the entropy (Buffer)
+ */
+export const mnemonicToEntropy = (module, mnemonic, language = 'english') => (
+  Buffer.from(callSecretResult(module, module.xbip39_mnemonic_to_entropy, { mnemonic, language }))
+);
+
+/**
+ * Get the mnemonic phrase of the given entropy.
+ *
+ * @param module   - the WASM module that is used for crypto operations
+ * @param entropy  - the entropy (16, 20, 24, 28 or 32 bytes)
+ * @param language - the language of the word list (default 'english')
+ * @returns {*}    - the mnemonic phrase (string)
+ */
+export const entropyToMnenomic = (module, entropy, language = 'english') => (
+  callSecretResult(module, module.xbip39_entropy_to_mnemonic, { entropy: Array.from(entropy), language })
+);
+
+/**
+ * Check the words and the checksum of the given mnemonic phrase.
+ *
+ * @param module   - the WASM module that is used for crypto operations
+ * @param mnemonic - the mnemonic phrase (string)
+ * @param language - the language of the word list (default 'english')
+ * @returns {*}    - true or false
+ */
+export const validateMnemonic = (module, mnemonic, language = 'english') => {
+  try {
+    return callSecretResult(module, module.xbip39_mnemonic_check, { mnemonic, language });
+  } catch (e) {
+    return false;
+  }
+};
+
+/**
+ * Get the standard BIP39 seed of the given mnemonic phrase.
+ *
+ * @param module   - the WASM module that is used for crypto operations
+ * @param mnemonic - the mnemonic phrase (string)
+ * @param password - the optional BIP39 password (string)
+ * @param language - the language of the word list (default 'english')
+ * @returns {*}    - the seed (64 bytes)
+ */
+export const mnemonicToSeed = (module, mnemonic, password = '', language = 'english') => (
+  new Uint8Array(callSecretResult(module, module.xbip39_mnemonic_to_seed, { mnemonic, password, language }))
 );
 
-export const entropyToMnenomic = (entropy) => (
-  bip39.entropyToMnemonic(entropy)
+/**
+ * Get the cardano wallet seed of the given mnemonic phrase, to use with
+ * `Wallet.fromSeed`.
+ *
+ * @param module   - the WASM module that is used for crypto operations
+ * @param mnemonic - the mnemonic phrase (string)
+ * @param language - the language of the word list (default 'english')
+ * @returns {*}    - the wallet seed (32 bytes)
+ */
+export const mnemonicToWalletSeed = (module, mnemonic, language = 'english') => (
+  callSecretResult(module, module.xbip39_mnemonic_to_wallet_seed, { mnemonic, language })
 );
 
 export default {
-  mnemonicToEntropy,
-  entropyToMnenomic,
+  LANGUAGES,
+  mnemonicToEntropy: apply(mnemonicToEntropy, RustModule),
+  entropyToMnenomic: apply(entropyToMnenomic, RustModule),
+  validateMnemonic: apply(validateMnemonic, RustModule),
+  mnemonicToSeed: apply(mnemonicToSeed, RustModule),
+  mnemonicToWalletSeed: apply(mnemonicToWalletSeed, RustModule),
 }
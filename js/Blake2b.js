@@ -1,14 +1,16 @@
 import RustModule from './RustModule';
 import { copyArray, newArray, newArray0 } from './utils/arrays';
 import { apply } from './utils/functions';
+import { checkStatus } from './utils/status';
 
 export const blake2b_256 = (module, message) => {
   let input = newArray(module, message);
   let output = newArray0(module, 32);
-  module.blake2b_256(input, message.length, output);
+  const rs = module.blake2b_256(input, message.length, output);
   let result = copyArray(module, output, 32);
-  module.dealloc(input);
-  module.dealloc(output);
+  module.dealloc(input, message.length);
+  module.dealloc(output, 32);
+  checkStatus(module, rs);
   return result
 };
 
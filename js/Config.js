@@ -2,6 +2,20 @@ export const defaultConfig = () => {
   return { protocol_magic: 764824073 };
 };
 
+/**
+ * Add the linear fee coefficients to the configuration: the fee of a
+ * transaction of `size` bytes is `a + b * size` lovelace.
+ *
+ * @param config - the configuration
+ * @param a      - the constant part of the fee (lovelace)
+ * @param b      - the fee per byte (lovelace)
+ * @returns {*}  - the new configuration
+ */
+export const withLinearFee = (config, a, b) => {
+  return Object.assign({}, config, { linear_fee: { a, b } });
+};
+
 export default {
-  defaultConfig: defaultConfig
+  defaultConfig: defaultConfig,
+  withLinearFee: withLinearFee
 };
@@ -0,0 +1,302 @@
+import iconv from 'iconv-lite';
+import RustModule from './RustModule';
+import { newArray, newArray0, copyArray, newOutput, copyOutput } from './utils/arrays';
+import { apply } from './utils/functions';
+import { checkStatus } from './utils/status';
+import { splitAmount } from './utils/amount';
+import { selectionPolicy } from './utils/selection';
+import { call } from './utils/jrpc';
+import { DEFAULT_SCHEME, schemeCode } from './utils/scheme';
+
+// Objects (keys, wallets, accounts and transactions) kept in the WASM module
+// and referred to by an integer handle. Every handle must be released with
+// the matching `*Free` function once it is not needed anymore.
+
+/**
+ * Import an extended private key in the module.
+ *
+ * @param module - the WASM module that is used for crypto operations
+ * @param xprv   - the extended private key (96 bytes)
+ * @returns {*}  - the handle of the private key
+ */
+export const xprvNew = (module, xprv) => {
+  const bufxprv = newArray(module, xprv);
+  const handle = module.hxprv_new(bufxprv);
+  module.dealloc_secret(bufxprv, xprv.length);
+  return checkStatus(module, handle);
+};
+
+/**
+ * Generate the root extended private key from the given seed.
+ *
+ * @param module - the WASM module that is used for crypto operations
+ * @param seed   - the 32 bytes seed
+ * @returns {*}  - the handle of the private key
+ */
+export const xprvFromSeed = (module, seed) => {
+  const bufseed = newArray(module, seed);
+  const handle = module.hxprv_from_seed(bufseed);
+  module.dealloc_secret(bufseed, seed.length);
+  return checkStatus(module, handle);
+};
+
+export const xprvDerive = (module, xprv, index, scheme = DEFAULT_SCHEME) => (
+  checkStatus(module, module.hxprv_derive(xprv, schemeCode(scheme), index))
+);
+
+/**
+ * Derive the private key at the given textual derivation path.
+ *
+ * @param module - the WASM module that is used for crypto operations
+ * @param xprv   - the handle of the private key
+ * @param path   - the derivation path (`m/44'/1815'/0'/0/1`)
+ * @param scheme - the derivation scheme ('V1' or 'V2', default 'V2')
+ * @returns {*}  - the handle of the derived private key
+ */
+export const xprvDerivePath = (module, xprv, path, scheme = DEFAULT_SCHEME) => {
+  const code = schemeCode(scheme);
+  const path_array = iconv.encode(path, 'utf8');
+  const bufpath = newArray(module, path_array);
+  const handle = module.hxprv_derive_path(xprv, code, bufpath, path_array.length);
+  module.dealloc(bufpath, path_array.length);
+  return checkStatus(module, handle);
+};
+
+export const xprvToPublic = (module, xprv) => (
+  checkStatus(module, module.hxprv_to_public(xprv))
+);
+
+export const xprvSign = (module, xprv, msg) => {
+  const bufmsg = newArray(module, msg);
+  const bufsig = newArray0(module, 64);
+  const rs = module.hxprv_sign(xprv, bufmsg, msg.length, bufsig);
+  let result = copyArray(module, bufsig, 64);
+  module.dealloc(bufmsg, msg.length);
+  module.dealloc(bufsig, 64);
+  checkStatus(module, rs);
+  return result;
+};
+
+export const xprvFree = (module, xprv) => {
+  checkStatus(module, module.hxprv_free(xprv));
+};
+
+export const xpubNew = (module, xpub) => {
+  const bufxpub = newArray(module, xpub);
+  const handle = module.hxpub_new(bufxpub);
+  module.dealloc(bufxpub, xpub.length);
+  return checkStatus(module, handle);
+};
+
+/**
+ * Copy the public key out of the module.
+ *
+ * @param module - the WASM module that is used for crypto operations
+ * @param xpub   - the handle of the public key
+ * @returns {*}  - the extended public key (64 bytes)
+ */
+export const xpubExport = (module, xpub) => {
+  const bufxpub = newArray0(module, 64);
+  const rs = module.hxpub_export(xpub, bufxpub);
+  let result = copyArray(module, bufxpub, 64);
+  module.dealloc(bufxpub, 64);
+  checkStatus(module, rs);
+  return result;
+};
+
+export const xpubDerive = (module, xpub, index, scheme = DEFAULT_SCHEME) => {
+  if (index >= 0x80000000) {
+    throw new Error('cannot do public derivation with hard index');
+  }
+  return checkStatus(module, module.hxpub_derive(xpub, schemeCode(scheme), index));
+};
+
+export const xpubFree = (module, xpub) => {
+  checkStatus(module, module.hxpub_free(xpub));
+};
+
+/**
+ * Start building a new transaction.
+ *
+ * @param module - the WASM module that is used for crypto operations
+ * @returns {*}  - the handle of the transaction
+ */
+export const txNew = (module) => (
+  checkStatus(module, module.htx_new())
+);
+
+/**
+ * Add an input to the transaction.
+ *
+ * @param module - the WASM module that is used for crypto operations
+ * @param tx     - the handle of the transaction
+ * @param txid   - the txid of the transaction to spend from (32 bytes)
+ * @param index  - the index of the output to spend in the transaction
+ */
+export const txAddInput = (module, tx, txid, index) => {
+  const buftxid = newArray(module, txid);
+  const rs = module.htx_add_input(tx, buftxid, index);
+  module.dealloc(buftxid, txid.length);
+  checkStatus(module, rs);
+};
+
+/**
+ * Add an output to the transaction.
+ *
+ * @param module - the WASM module that is used for crypto operations
+ * @param tx     - the handle of the transaction
+ * @param addr   - the address to send the given amount to (cbor bytes)
+ * @param amount - the amount (lovelace) to send to the given address, a
+ *                 safe integer or a string of decimal digits
+ */
+export const txAddOutput = (module, tx, addr, amount) => {
+  const [amount_lo, amount_hi] = splitAmount(amount);
+  const bufaddr = newArray(module, addr);
+  const rs = module.htx_add_output(tx, bufaddr, addr.length, amount_lo, amount_hi);
+  module.dealloc(bufaddr, addr.length);
+  checkStatus(module, rs);
+};
+
+export const txToCbor = (module, tx) => {
+  const bufout = newOutput(module);
+  const rsz = module.htx_to_cbor(tx, bufout);
+  let out = copyOutput(module, bufout, rsz);
+  checkStatus(module, rsz);
+  return out;
+};
+
+/**
+ * Sign the transaction, this function returns the signature, not the TxInWitness
+ *
+ * @param module - the WASM module that is used for crypto operations
+ * @param config - the configuration
+ * @param xprv   - the handle of the private key to sign the transaction with
+ * @param tx     - the handle of the transaction
+ * @returns {*}  - the signature
+ */
+export const txSign = (module, config, xprv, tx) => {
+  const config_array = iconv.encode(JSON.stringify(config), 'utf8');
+  const bufcfg = newArray(module, config_array);
+  const bufsig = newArray0(module, 64);
+  const rs = module.htx_sign(bufcfg, config_array.length, xprv, tx, bufsig);
+  let result = copyArray(module, bufsig, 64);
+  module.dealloc(bufsig, 64);
+  module.dealloc(bufcfg, config_array.length);
+  checkStatus(module, rs);
+  return result;
+};
+
+export const txFree = (module, tx) => {
+  checkStatus(module, module.htx_free(tx));
+};
+
+/**
+ * Create a wallet from the given seed, the wallet stays in the module.
+ *
+ * @param module - the WASM module that is used for crypto operations
+ * @param seed   - the 32 bytes seed to generate the wallet from
+ * @param scheme - the derivation scheme ('V1' or 'V2', default 'V2')
+ * @returns {*}  - the handle of the wallet
+ */
+export const walletNew = (module, seed, scheme = DEFAULT_SCHEME) => {
+  const code = schemeCode(scheme);
+  const bufseed = newArray(module, seed);
+  const handle = module.hwallet_new(bufseed, code);
+  module.dealloc_secret(bufseed, seed.length);
+  return checkStatus(module, handle);
+};
+
+/**
+ * Import a wallet object (as created by `Wallet.fromSeed`) in the module.
+ *
+ * @param module - the WASM module that is used for crypto operations
+ * @param wallet - the wallet object
+ * @returns {*}  - the handle of the wallet
+ */
+export const walletImport = (module, wallet) => {
+  const input_array = iconv.encode(JSON.stringify(wallet), 'utf8');
+  const bufinput = newArray(module, input_array);
+  const handle = module.hwallet_import(bufinput, input_array.length);
+  module.dealloc_secret(bufinput, input_array.length);
+  return checkStatus(module, handle);
+};
+
+export const walletAccount = (module, wallet, account) => (
+  checkStatus(module, module.hwallet_account(wallet, account))
+);
+
+/**
+ * Same as `Wallet.spend` but with the handle of the wallet.
+ */
+export const walletSpend = (module, wallet, inputs, outputs, change_addr, policy, linear_fee) => (
+  call(module, module.xhwallet_spend, {
+    wallet, inputs, outputs, change_addr, selection_policy: selectionPolicy(policy), linear_fee
+  })
+);
+
+/**
+ * Same as `Wallet.sweep` but with the handle of the wallet.
+ */
+export const walletSweep = (module, wallet, inputs, destination, linear_fee) => (
+  call(module, module.xhwallet_sweep, { wallet, inputs, destination, linear_fee })
+);
+
+export const walletFree = (module, wallet) => {
+  checkStatus(module, module.hwallet_free(wallet));
+};
+
+/**
+ * Same as `Wallet.generateAddresses` but with the handle of the account.
+ */
+export const accountAddresses = (module, account, type, indices, scheme = DEFAULT_SCHEME) => (
+  call(module, module.xhaccount_addresses, { account, derivation_scheme: scheme, address_type: type, indices })
+);
+
+/**
+ * Create a watch-only account from the account public key.
+ *
+ * @param module - the WASM module that is used for crypto operations
+ * @param xpub   - the account public key (64 bytes)
+ * @param scheme - the derivation scheme ('V1' or 'V2', default 'V2')
+ * @returns {*}  - the handle of the account
+ */
+export const accountFromXPub = (module, xpub, scheme = DEFAULT_SCHEME) => {
+  const code = schemeCode(scheme);
+  const bufxpub = newArray(module, xpub);
+  const handle = module.haccount_from_xpub(bufxpub, code);
+  module.dealloc(bufxpub, xpub.length);
+  return checkStatus(module, handle);
+};
+
+export const accountFree = (module, account) => {
+  checkStatus(module, module.haccount_free(account));
+};
+
+export default {
+  xprvNew: apply(xprvNew, RustModule),
+  xprvFromSeed: apply(xprvFromSeed, RustModule),
+  xprvDerive: apply(xprvDerive, RustModule),
+  xprvDerivePath: apply(xprvDerivePath, RustModule),
+  xprvToPublic: apply(xprvToPublic, RustModule),
+  xprvSign: apply(xprvSign, RustModule),
+  xprvFree: apply(xprvFree, RustModule),
+  xpubNew: apply(xpubNew, RustModule),
+  xpubExport: apply(xpubExport, RustModule),
+  xpubDerive: apply(xpubDerive, RustModule),
+  xpubFree: apply(xpubFree, RustModule),
+  txNew: apply(txNew, RustModule),
+  txAddInput: apply(txAddInput, RustModule),
+  txAddOutput: apply(txAddOutput, RustModule),
+  txToCbor: apply(txToCbor, RustModule),
+  txSign: apply(txSign, RustModule),
+  txFree: apply(txFree, RustModule),
+  walletNew: apply(walletNew, RustModule),
+  walletImport: apply(walletImport, RustModule),
+  walletAccount: apply(walletAccount, RustModule),
+  walletSpend: apply(walletSpend, RustModule),
+  walletSweep: apply(walletSweep, RustModule),
+  walletFree: apply(walletFree, RustModule),
+  accountAddresses: apply(accountAddresses, RustModule),
+  accountFromXPub: apply(accountFromXPub, RustModule),
+  accountFree: apply(accountFree, RustModule),
+};
@@ -1,101 +1,312 @@
+import iconv from 'iconv-lite';
 import RustModule from './RustModule';
-import { newArray, newArray0, copyArray } from './utils/arrays';
+import { newArray, newArray0, copyArray, newArrayU32, newOutput, copyOutput } from './utils/arrays';
 import { apply } from './utils/functions';
+import { checkStatus, FAILED } from './utils/status';
+import { DEFAULT_SCHEME, schemeCode } from './utils/scheme';
+import { callResult } from './utils/jrpc';
 
 export const fromSeed = (module, seed) => {
   const bufseed = newArray(module, seed);
   const bufxprv = newArray0(module, 96);
-  module.wallet_from_seed(bufseed, bufxprv);
+  const rs = module.wallet_from_seed(bufseed, bufxprv);
   let result = copyArray(module, bufxprv, 96);
-  module.dealloc(bufseed);
-  module.dealloc(bufxprv);
+  module.dealloc_secret(bufseed, seed.length);
+  module.dealloc_secret(bufxprv, 96);
+  checkStatus(module, rs);
   return result;
 };
 
 export const toPublic = (module, xprv) => {
   const bufxprv = newArray(module, xprv);
   const bufxpub = newArray0(module, 64);
-  module.wallet_to_public(bufxprv, bufxpub);
+  const rs = module.wallet_to_public(bufxprv, bufxpub);
   let result = copyArray(module, bufxpub, 64);
-  module.dealloc(bufxprv);
-  module.dealloc(bufxpub);
+  module.dealloc_secret(bufxprv, xprv.length);
+  module.dealloc(bufxpub, 64);
+  checkStatus(module, rs);
   return result;
 };
 
-export const derivePrivate = (module, xprv, index) => {
+export const derivePrivate = (module, xprv, index, scheme = DEFAULT_SCHEME) => {
+  const code = schemeCode(scheme);
   const bufxprv = newArray(module, xprv);
   const bufchild = newArray0(module, xprv.length);
-  module.wallet_derive_private(bufxprv, index, bufchild);
+  const rs = module.wallet_derive_private(bufxprv, code, index, bufchild);
   let result = copyArray(module, bufchild, xprv.length);
-  module.dealloc(bufxprv);
-  module.dealloc(bufchild);
+  module.dealloc_secret(bufxprv, xprv.length);
+  module.dealloc_secret(bufchild, xprv.length);
+  checkStatus(module, rs);
   return result;
 };
 
-export const derivePublic = (module, xpub, index) => {
+export const derivePublic = (module, xpub, index, scheme = DEFAULT_SCHEME) => {
   if (index >= 0x80000000) {
     throw new Error('cannot do public derivation with hard index');
   }
+  const code = schemeCode(scheme);
   const bufxpub = newArray(module, xpub);
   const bufchild = newArray0(module, xpub.length);
-  const r = module.wallet_derive_public(bufxpub, index, bufchild);
+  const r = module.wallet_derive_public(bufxpub, code, index, bufchild);
   const result = copyArray(module, bufchild, xpub.length);
-  module.dealloc(bufxpub);
-  module.dealloc(bufchild);
+  module.dealloc(bufxpub, xpub.length);
+  module.dealloc(bufchild, xpub.length);
+  checkStatus(module, r);
   return result
 };
 
+// copy the derivation path in the module: the textual form
+// (`m/44'/1815'/0'/0/1`) or an array of indices
+const newPath = (module, path) => {
+  if (typeof path === 'string') {
+    const path_array = iconv.encode(path, 'utf8');
+    return { ptr: newArray(module, path_array), length: path_array.length, capacity: path_array.length, text: true };
+  }
+  return { ptr: newArrayU32(module, path), length: path.length, capacity: path.length * 4, text: false };
+};
+
+/**
+ * Derive the private key at the given derivation path.
+ *
+ * @param module - the WASM module that is used for crypto operations
+ * @param xprv   - the extended private key (96 bytes)
+ * @param path   - the derivation path: `m/44'/1815'/0'/0/1` or an array of indices
+ * @param scheme - the derivation scheme ('V1' or 'V2', default 'V2')
+ * @returns {*}  - the derived extended private key (96 bytes)
+ */
+export const derivePrivatePath = (module, xprv, path, scheme = DEFAULT_SCHEME) => {
+  const code = schemeCode(scheme);
+  const bufxprv = newArray(module, xprv);
+  const bufchild = newArray0(module, xprv.length);
+  const bufpath = newPath(module, path);
+  const rs = bufpath.text
+    ? module.wallet_derive_private_path(bufxprv, code, bufpath.ptr, bufpath.length, bufchild)
+    : module.wallet_derive_private_indices(bufxprv, code, bufpath.ptr, bufpath.length, bufchild);
+  let result = copyArray(module, bufchild, xprv.length);
+  module.dealloc_secret(bufxprv, xprv.length);
+  module.dealloc_secret(bufchild, xprv.length);
+  module.dealloc(bufpath.ptr, bufpath.capacity);
+  checkStatus(module, rs);
+  return result;
+};
+
+/**
+ * Derive the public key at the given derivation path, the path cannot
+ * contain hardened indices.
+ *
+ * @param module - the WASM module that is used for crypto operations
+ * @param xpub   - the extended public key (64 bytes)
+ * @param path   - the derivation path: `m/0/1` or an array of indices
+ * @param scheme - the derivation scheme ('V1' or 'V2', default 'V2')
+ * @returns {*}  - the derived extended public key (64 bytes)
+ */
+export const derivePublicPath = (module, xpub, path, scheme = DEFAULT_SCHEME) => {
+  const code = schemeCode(scheme);
+  const bufxpub = newArray(module, xpub);
+  const bufchild = newArray0(module, xpub.length);
+  const bufpath = newPath(module, path);
+  const rs = bufpath.text
+    ? module.wallet_derive_public_path(bufxpub, code, bufpath.ptr, bufpath.length, bufchild)
+    : module.wallet_derive_public_indices(bufxpub, code, bufpath.ptr, bufpath.length, bufchild);
+  let result = copyArray(module, bufchild, xpub.length);
+  module.dealloc(bufxpub, xpub.length);
+  module.dealloc(bufchild, xpub.length);
+  module.dealloc(bufpath.ptr, bufpath.capacity);
+  checkStatus(module, rs);
+  return result;
+};
+
+/**
+ * Derive the `count` children of the given public key, starting at the
+ * index `start`, in one call to the module.
+ *
+ * @param module - the WASM module that is used for crypto operations
+ * @param xpub   - the extended public key (64 bytes)
+ * @param start  - the index of the first child
+ * @param count  - the number of children to derive (at most 10000)
+ * @param scheme - the derivation scheme ('V1' or 'V2', default 'V2')
+ * @returns {*}  - the array of the derived extended public keys
+ */
+export const derivePublicRange = (module, xpub, start, count, scheme = DEFAULT_SCHEME) => {
+  const code = schemeCode(scheme);
+  const bufxpub = newArray(module, xpub);
+  const bufout = newOutput(module);
+  const rs = module.wallet_derive_public_range(bufxpub, code, start, count, bufout);
+  let keys = copyOutput(module, bufout, rs);
+  module.dealloc(bufxpub, xpub.length);
+  checkStatus(module, rs);
+  let result = [];
+  for (let i = 0; i < count; i++) {
+    result.push(keys.slice(i * 64, (i + 1) * 64));
+  }
+  return result;
+};
+
 export const sign = (module, xprv, msg) => {
   let length = msg.length;
   const bufsig = newArray0(module, 64);
   const bufxprv = newArray(module, xprv);
   const bufmsg = newArray(module, msg);
-  module.wallet_sign(bufxprv, bufmsg, length, bufsig);
+  const rs = module.wallet_sign(bufxprv, bufmsg, length, bufsig);
   let result = copyArray(module, bufsig, 64);
-  module.dealloc(bufxprv);
-  module.dealloc(bufmsg);
-  module.dealloc(bufsig);
+  module.dealloc_secret(bufxprv, xprv.length);
+  module.dealloc(bufmsg, msg.length);
+  module.dealloc(bufsig, 64);
+  checkStatus(module, rs);
   return result
 };
 
 export const publicKeyToAddress = (module, xpub, payload) => {
   const bufxpub    = newArray(module, xpub);
   const bufpayload = newArray(module, payload);
-  const bufaddr    = newArray0(module, 1024);
+  const bufaddr    = newOutput(module);
 
   let rs = module.wallet_public_to_address(bufxpub, bufpayload, payload.length, bufaddr);
-  let addr = copyArray(module, bufaddr, rs);
+  let addr = copyOutput(module, bufaddr, rs);
 
-  module.dealloc(bufaddr);
-  module.dealloc(bufpayload);
-  module.dealloc(bufxpub);
+  module.dealloc(bufpayload, payload.length);
+  module.dealloc(bufxpub, xpub.length);
 
+  checkStatus(module, rs);
   return addr;
 };
 
 export const addressGetPayload = (module, address) => {
   const bufaddr    = newArray(module, address);
-  const bufpayload = newArray0(module, 1024);
+  const bufpayload = newOutput(module);
 
   let rs = module.wallet_address_get_payload(bufaddr, address.length, bufpayload);
-  let payload = null;
-  if (rs > 0) {
-      payload = copyArray(module, bufpayload, rs);
+  let payload = copyOutput(module, bufpayload, rs);
+  if (rs === 0) {
+      payload = null;
   }
 
-  module.dealloc(bufpayload);
-  module.dealloc(bufaddr);
+  module.dealloc(bufaddr, address.length);
 
+  checkStatus(module, rs);
   return payload;
 };
 
+/**
+ * Create the script address (ATScript) of an m-of-n multisig: spending from
+ * it requires the signatures of `required` of the given public keys (see
+ * `Tx.assemble`). The validator is a script of this module, not a Plutus
+ * core program.
+ *
+ * @param module      - the WASM module that is used for crypto operations
+ * @param required    - the number of signatures required (m)
+ * @param public_keys - the public keys allowed to sign (n, at most 255)
+ * @returns {*}       - { address, validator }, the address in CBOR and the
+ *                      validator script ({ version, script })
+ */
+export const multisigAddress = (module, required, public_keys) => (
+  callResult(module, module.xaddress_multisig, {
+    required,
+    public_keys: public_keys.map(key => Array.from(key))
+  })
+);
+
+/**
+ * Create the script address (ATScript) of the given script.
+ *
+ * @param module  - the WASM module that is used for crypto operations
+ * @param version - the version of the script
+ * @param script  - the script (array of bytes)
+ * @returns {*}   - the address in CBOR
+ */
+export const scriptToAddress = (module, version, script) => {
+  const bufscript = newArray(module, script);
+  const bufaddr   = newOutput(module);
+
+  let rs = module.wallet_script_to_address(version, bufscript, script.length, bufaddr);
+  let addr = copyOutput(module, bufaddr, rs);
+
+  module.dealloc(bufscript, script.length);
+
+  checkStatus(module, rs);
+  return addr;
+};
+
+/**
+ * Check the address belongs to the public key, without decrypting the
+ * payload of the address.
+ *
+ * @param module  - the WASM module that is used for crypto operations
+ * @param xpub    - the public key
+ * @param address - the address in CBOR
+ * @returns {*}   - true or false
+ */
+export const addressIsOf = (module, xpub, address) => {
+  const bufxpub = newArray(module, xpub);
+  const bufaddr = newArray(module, address);
+
+  let rs = module.wallet_address_is_of(bufxpub, bufaddr, address.length);
+
+  module.dealloc(bufaddr, address.length);
+  module.dealloc(bufxpub, xpub.length);
+
+  if (rs !== FAILED) {
+      checkStatus(module, rs);
+  }
+  return rs !== FAILED;
+};
+
+/**
+ * Decode the address given in base58 (e.g. `DdzFF...`), checking its CRC32.
+ *
+ * @param module  - the WASM module that is used for crypto operations
+ * @param address - the address in base58 (a string)
+ * @returns {*}   - the address in CBOR
+ */
+export const addressFromBase58 = (module, address) => {
+  const address_array = iconv.encode(address, 'utf8');
+  const bufstr  = newArray(module, address_array);
+  const bufaddr = newOutput(module);
+
+  let rs = module.wallet_address_from_base58(bufstr, address_array.length, bufaddr);
+  let addr = copyOutput(module, bufaddr, rs);
+
+  module.dealloc(bufstr, address_array.length);
+
+  checkStatus(module, rs);
+  return addr;
+};
+
+/**
+ * Encode the address given in CBOR in base58.
+ *
+ * @param module  - the WASM module that is used for crypto operations
+ * @param address - the address in CBOR
+ * @returns {*}   - the address in base58 (a string)
+ */
+export const addressToBase58 = (module, address) => {
+  const bufaddr = newArray(module, address);
+  const bufstr  = newOutput(module);
+
+  let rs = module.wallet_address_to_base58(bufaddr, address.length, bufstr);
+  let str = copyOutput(module, bufstr, rs);
+
+  module.dealloc(bufaddr, address.length);
+
+  checkStatus(module, rs);
+  return iconv.decode(Buffer.from(str), 'utf8');
+};
+
 
 export default {
   fromSeed: apply(fromSeed, RustModule),
   toPublic: apply(toPublic, RustModule),
   derivePrivate: apply(derivePrivate, RustModule),
   derivePublic: apply(derivePublic, RustModule),
+  derivePrivatePath: apply(derivePrivatePath, RustModule),
+  derivePublicPath: apply(derivePublicPath, RustModule),
+  derivePublicRange: apply(derivePublicRange, RustModule),
   sign: apply(sign, RustModule),
   publicKeyToAddress: apply(publicKeyToAddress, RustModule),
-  addressGetPayload: apply(addressGetPayload, RustModule)
+  addressGetPayload: apply(addressGetPayload, RustModule),
+  scriptToAddress: apply(scriptToAddress, RustModule),
+  multisigAddress: apply(multisigAddress, RustModule),
+  addressIsOf: apply(addressIsOf, RustModule),
+  addressFromBase58: apply(addressFromBase58, RustModule),
+  addressToBase58: apply(addressToBase58, RustModule)
 };
@@ -2,6 +2,7 @@ import iconv from 'iconv-lite';
 import RustModule from './RustModule';
 import { newArray, newArray0, copyArray } from './utils/arrays';
 import { apply } from './utils/functions';
+import { checkStatus } from './utils/status';
 import Bip39 from './Bip39';
 
 export const scramble = (module, iv, password, input) => {
@@ -12,12 +13,13 @@ export const scramble = (module, iv, password, input) => {
   const bufinput = newArray(module, input);
   const bufpassword = newArray(module, password);
   const bufoutput = newArray0(module, input.length + 8);
-  module.paper_scramble(bufiv, bufpassword, password.length, bufinput, input.length, bufoutput);
+  const rs = module.paper_scramble(bufiv, bufpassword, password.length, bufinput, input.length, bufoutput);
   let result = copyArray(module, bufoutput, input.length + 8);
-  module.dealloc(bufiv);
-  module.dealloc(bufinput);
-  module.dealloc(bufpassword);
-  module.dealloc(bufoutput);
+  module.dealloc(bufiv, iv.length);
+  module.dealloc_secret(bufinput, input.length);
+  module.dealloc_secret(bufpassword, password.length);
+  module.dealloc(bufoutput, input.length + 8);
+  checkStatus(module, rs);
   return result;
 };
 
@@ -42,11 +44,12 @@ export const unscramble = (module, password, input) => {
   const bufinput = newArray(module, input);
   const bufpassword = newArray(module, password);
   const bufoutput = newArray0(module, input.length - 8);
-  module.paper_unscramble(bufpassword, password.length, bufinput, input.length, bufoutput);
+  const rs = module.paper_unscramble(bufpassword, password.length, bufinput, input.length, bufoutput);
   let result = copyArray(module, bufoutput, input.length - 8);
-  module.dealloc(bufinput);
-  module.dealloc(bufpassword);
-  module.dealloc(bufoutput);
+  module.dealloc(bufinput, input.length);
+  module.dealloc_secret(bufpassword, password.length);
+  module.dealloc_secret(bufoutput, input.length - 8);
+  checkStatus(module, rs);
   return result;
 };
 
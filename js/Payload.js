@@ -1,6 +1,8 @@
 import RustModule from './RustModule';
-import { newArray, newArray0, copyArray, newArrayU32, newArrayU32_0, copyArrayU32 } from './utils/arrays';
+import { newArray, newArray0, copyArray, newArrayU32, newOutput, copyOutput, copyOutputU32 } from './utils/arrays';
 import { apply } from './utils/functions';
+import { checkStatus, FAILED } from './utils/status';
+import { callResult } from './utils/jrpc';
 
 
 /**
@@ -16,11 +18,12 @@ export const initialise = (module, xpub) => {
   const bufxpub  = newArray(module, xpub);
   const bufhdkey = newArray0(module, 32);
 
-  module.wallet_payload_initiate(bufxpub, bufhdkey);
+  const rs = module.wallet_payload_initiate(bufxpub, bufhdkey);
   let key = copyArray(module, bufhdkey, 32);
 
-  module.dealloc(bufxpub);
-  module.dealloc(bufhdkey);
+  module.dealloc(bufxpub, xpub.length);
+  module.dealloc(bufhdkey, 32);
+  checkStatus(module, rs);
   return key;
 };
 
@@ -35,15 +38,15 @@ export const initialise = (module, xpub) => {
 export const encrypt_derivation_path = (module, key, derivation_path) => {
   const bufhdkey = newArray(module, key);
   const bufpath  = newArrayU32(module, derivation_path);
-  const bufenc   = newArray0(module, 1024);
+  const bufenc   = newOutput(module);
 
   let rsz = module.wallet_payload_encrypt(bufhdkey, bufpath, derivation_path.length, bufenc);
-  let enc = copyArray(module, bufenc, rsz);
+  let enc = copyOutput(module, bufenc, rsz);
 
-  module.dealloc(bufhdkey);
-  module.dealloc(bufpath);
-  module.dealloc(bufenc);
+  module.dealloc(bufhdkey, key.length);
+  module.dealloc(bufpath, derivation_path.length * 4);
 
+  checkStatus(module, rsz);
   return enc;
 }
 
@@ -58,24 +61,41 @@ export const encrypt_derivation_path = (module, key, derivation_path) => {
 export const decrypt_derivation_path = (module, key, payload) => {
   const bufhdkey   = newArray(module, key);
   const bufpayload = newArray(module, payload);
-  const bufdec     = newArrayU32_0(module, 64);
-
-  let dec = null;
+  const bufdec     = newOutput(module);
 
   let rsz = module.wallet_payload_decrypt(bufhdkey, bufpayload, payload.length, bufdec);
-  if (rsz !== -1) {
-      dec = copyArrayU32(module, bufdec, rsz);
-  }
+  let dec = copyOutputU32(module, bufdec, rsz);
 
-  module.dealloc(bufhdkey);
-  module.dealloc(bufpayload);
-  module.dealloc(bufdec);
+  module.dealloc(bufhdkey, key.length);
+  module.dealloc(bufpayload, payload.length);
 
+  if (rsz !== FAILED) {
+      checkStatus(module, rsz);
+  }
   return dec;
 }
 
+/**
+ * Decrypt the derivation paths of the given addresses in one call. The
+ * addresses whose payload could not be decrypted with the key (addresses
+ * of another wallet, without payload or not valid) are left out.
+ *
+ * @param module - the WASM module that is used for crypto operations
+ * @param key - the encryption key initialised with `initialise`
+ * @param addresses - the addresses (in CBOR)
+ * @returns {*} - the decrypted addresses: [{ index, path }], `index` being
+ *                the position of the address in `addresses`
+ */
+export const decrypt_addresses = (module, key, addresses) => (
+  callResult(module, module.xpayload_decrypt_addresses, {
+    key: Array.from(key),
+    addresses: addresses.map(address => Array.from(address))
+  })
+);
+
 export default {
   initialise: apply(initialise, RustModule),
   encrypt_derivation_path: apply(encrypt_derivation_path, RustModule),
-  decrypt_derivation_path: apply(decrypt_derivation_path, RustModule)
+  decrypt_derivation_path: apply(decrypt_derivation_path, RustModule),
+  decrypt_addresses: apply(decrypt_addresses, RustModule)
 }
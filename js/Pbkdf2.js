@@ -0,0 +1,53 @@
+import RustModule from './RustModule';
+import { newArray, newArray0, copyArray } from './utils/arrays';
+import { apply } from './utils/functions';
+import { checkStatus } from './utils/status';
+
+const pbkdf2 = (module, fn, password, salt, iters, output) => {
+  const bufpassword = newArray(module, password);
+  const bufsalt     = newArray(module, salt);
+  const bufoutput   = newArray0(module, output);
+
+  const rs = fn(bufpassword, password.length, bufsalt, salt.length, iters, output, bufoutput);
+  let result = copyArray(module, bufoutput, output);
+
+  module.dealloc_secret(bufpassword, password.length);
+  module.dealloc(bufsalt, salt.length);
+  module.dealloc_secret(bufoutput, output);
+
+  checkStatus(module, rs);
+  return result;
+};
+
+/**
+ * Derive a key from the given password with PBKDF2-HMAC-SHA256.
+ *
+ * @param module   - the WASM module that is used for crypto operations
+ * @param password - the password (array of bytes)
+ * @param salt     - the salt (array of bytes)
+ * @param iters    - the number of iterations
+ * @param output   - the number of bytes to derive
+ * @returns {*}    - the derived key
+ */
+export const sha256 = (module, password, salt, iters, output) => (
+  pbkdf2(module, module.pbkdf2_sha256, password, salt, iters, output)
+);
+
+/**
+ * Derive a key from the given password with PBKDF2-HMAC-SHA512.
+ *
+ * @param module   - the WASM module that is used for crypto operations
+ * @param password - the password (array of bytes)
+ * @param salt     - the salt (array of bytes)
+ * @param iters    - the number of iterations
+ * @param output   - the number of bytes to derive
+ * @returns {*}    - the derived key
+ */
+export const sha512 = (module, password, salt, iters, output) => (
+  pbkdf2(module, module.pbkdf2_sha512, password, salt, iters, output)
+);
+
+export default {
+  sha256: apply(sha256, RustModule),
+  sha512: apply(sha512, RustModule),
+}
@@ -1,8 +1,12 @@
 import iconv from 'iconv-lite';
 import RustModule from './RustModule';
-import { newArray, newArray0, copyArray } from './utils/arrays';
+import { newArray, newArray0, copyArray, newOutput, copyOutput } from './utils/arrays';
 import { apply } from './utils/functions';
 import { base16 } from './utils/strings';
+import { checkStatus, FAILED } from './utils/status';
+import { call, callResult, callSecret } from './utils/jrpc';
+import { splitAmount, amountString } from './utils/amount';
+import { selectionPolicy } from './utils/selection';
 
 /**
  * Create a TxIn from the given TxId and the Index
@@ -14,14 +18,14 @@ import { base16 } from './utils/strings';
  */
 export const newTxIn = (module, txid, index) => {
         const buftxid = newArray(module, txid);
-        const buftxin = newArray0(module, 1024);
+        const buftxin = newOutput(module);
 
         let rsz = module.wallet_txin_create(buftxid, index, buftxin);
-        let txin = copyArray(module, buftxin, rsz);
+        let txin = copyOutput(module, buftxin, rsz);
 
-        module.dealloc(buftxin);
-        module.dealloc(buftxid);
+        module.dealloc(buftxid, txid.length);
 
+        checkStatus(module, rsz);
         return txin;
 };
 
@@ -30,19 +34,21 @@ export const newTxIn = (module, txid, index) => {
  *
  * @param module - the WASM module that is used for crypto operations
  * @param addr - the address to send the given amount to
- * @param amount - the amount to send to the given address
+ * @param amount - the amount (lovelace) to send to the given address, a safe
+ *                 integer or a string of decimal digits for the larger amounts
  * @returns {*} - a txout (encoded in cbor)
  */
 export const newTxOut = (module, addr, amount) => {
+        const [amount_lo, amount_hi] = splitAmount(amount);
         const bufaddr = newArray(module, addr);
-        const buftxout = newArray0(module, 1024);
+        const buftxout = newOutput(module);
 
-        let rsz = module.wallet_txout_create(bufaddr, addr.length, amount, buftxout);
-        let txout = copyArray(module, buftxout, rsz);
+        let rsz = module.wallet_txout_create(bufaddr, addr.length, amount_lo, amount_hi, buftxout);
+        let txout = copyOutput(module, buftxout, rsz);
 
-        module.dealloc(buftxout);
-        module.dealloc(bufaddr);
+        module.dealloc(bufaddr, addr.length);
 
+        checkStatus(module, rsz);
         return txout;
 };
 
@@ -53,13 +59,12 @@ export const newTxOut = (module, addr, amount) => {
  * @returns {*} - a tx (encoded in cbor)
  */
 export const create = (module) => {
-        const buftx = newArray0(module, 1024);
+        const buftx = newOutput(module);
 
         let rsz = module.wallet_tx_new(buftx);
-        let tx = copyArray(module, buftx, rsz);
-
-        module.dealloc(buftx);
+        let tx = copyOutput(module, buftx, rsz);
 
+        checkStatus(module, rsz);
         return tx;
 };
 
@@ -75,15 +80,15 @@ export const addInput = (module, tx, txin) => {
         const buftx = newArray(module, tx);
         const buftxin = newArray(module, txin);
 
-        const bufout = newArray0(module, 1024);
+        const bufout = newOutput(module);
 
         let rsz = module.wallet_tx_add_txin(buftx, tx.length, buftxin, txin.length, bufout);
-        let out = copyArray(module, bufout, rsz);
+        let out = copyOutput(module, bufout, rsz);
 
-        module.dealloc(bufout);
-        module.dealloc(buftxin);
-        module.dealloc(buftx);
+        module.dealloc(buftxin, txin.length);
+        module.dealloc(buftx, tx.length);
 
+        checkStatus(module, rsz);
         return out;
 };
 
@@ -99,15 +104,15 @@ export const addOutput = (module, tx, txout) => {
         const buftx = newArray(module, tx);
         const buftxout = newArray(module, txout);
 
-        const bufout = newArray0(module, 1024);
+        const bufout = newOutput(module);
 
         let rsz = module.wallet_tx_add_txout(buftx, tx.length, buftxout, txout.length, bufout);
-        let out = copyArray(module, bufout, rsz);
+        let out = copyOutput(module, bufout, rsz);
 
-        module.dealloc(bufout);
-        module.dealloc(buftxout);
-        module.dealloc(buftx);
+        module.dealloc(buftxout, txout.length);
+        module.dealloc(buftx, tx.length);
 
+        checkStatus(module, rsz);
         return out;
 };
 
@@ -130,14 +135,15 @@ export const sign = (module, config, tx, xprv) => {
         const bufcfg  = newArray(module, config_array);
         const bufsig = newArray0(module, 64);
 
-        module.wallet_tx_sign(bufcfg, config_array.length, bufxprv, buftx, tx.length, bufsig);
+        const rs = module.wallet_tx_sign(bufcfg, config_array.length, bufxprv, buftx, tx.length, bufsig);
         let result = copyArray(module, bufsig, 64);
 
-        module.dealloc(bufsig);
-        module.dealloc(bufcfg);
-        module.dealloc(bufxprv);
-        module.dealloc(buftx);
+        module.dealloc(bufsig, 64);
+        module.dealloc(bufcfg, config_array.length);
+        module.dealloc_secret(bufxprv, xprv.length);
+        module.dealloc(buftx, tx.length);
 
+        checkStatus(module, rs);
         return result
 };
 
@@ -162,14 +168,215 @@ export const verify = (module, config, tx, xpub, signature) => {
 
         let result = module.wallet_tx_verify(bufcfg, config_array.length, bufxpub, buftx, tx.length, bufsig);
 
-        module.dealloc(bufsig);
-        module.dealloc(bufcfg);
-        module.dealloc(bufxpub);
-        module.dealloc(buftx);
+        module.dealloc(bufsig, signature.length);
+        module.dealloc(bufcfg, config_array.length);
+        module.dealloc(bufxpub, xpub.length);
+        module.dealloc(buftx, tx.length);
+
+        if (result === FAILED) {
+                return false;
+        }
+        return checkStatus(module, result) === 0
+};
 
-        return result === 0
+/**
+ * Decode the CBOR of a signed transaction (TxAux), a transaction, a TxIn
+ * or a TxOut to a readable object.
+ *
+ * @param module - the WASM module that is used for crypto operations
+ * @param bytes  - the CBOR bytes
+ * @returns {*}  - the decoded object, its `type` is one of 'TxAux', 'Tx',
+ *                 'TxIn' or 'TxOut'
+ */
+export const decode = (module, bytes) => (
+  callResult(module, module.xtx_decode, Array.from(bytes))
+);
+
+/**
+ * Sign every input of the given transaction.
+ *
+ * @example
+ * ```
+ * // with the private keys of the inputs
+ * CardanoCrypto.Tx.signInputs(config, tx, [xprv0, xprv1]);
+ * // with the BIP44 addressing of the inputs in the wallet
+ * CardanoCrypto.Tx.signInputs(config, tx, { wallet, addressing: [addressing0, addressing1] });
+ * ```
+ *
+ * @param module  - the WASM module that is used for crypto operations
+ * @param config  - the configuration
+ * @param tx      - the transaction to sign (encoded in cbor)
+ * @param signers - the private keys of the inputs (in the order of the
+ *                  inputs) or the wallet and the addressing of the inputs
+ * @returns {*}   - { failed, result: { id, cbor_encoded_tx } }, the signed
+ *                  transaction (TxAux) ready to be sent
+ */
+export const signInputs = (module, config, tx, signers) => {
+  const input_signers = Array.isArray(signers)
+    ? { keys: signers.map((xprv) => Array.from(xprv)) }
+    : { wallet: signers };
+  return callSecret(module, module.xtx_sign, { config, tx: Array.from(tx), signers: input_signers });
+};
+
+/**
+ * Verify a signed transaction against the outputs it spends: the number
+ * of witnesses, the keys and the signatures of the witnesses, the fee and
+ * the duplicated inputs. The validator scripts of the script witnesses are
+ * checked against the spent addresses, only the multisig scripts are
+ * evaluated.
+ *
+ * @param module   - the WASM module that is used for crypto operations
+ * @param config   - the configuration
+ * @param txaux    - the signed transaction (encoded in cbor)
+ * @param resolved - the outputs ({ address, value }) spent by the inputs,
+ *                   in the order of the inputs
+ * @returns {*}    - the list of the violations (`{ violation, ... }`), empty
+ *                   if the transaction is valid
+ */
+export const verifyAll = (module, config, txaux, resolved) => (
+  callResult(module, module.xtx_verify, { config, tx: Array.from(txaux), resolved })
+);
+
+/**
+ * Estimate the fee and the size of a transaction once signed, before
+ * signing it.
+ *
+ * @example
+ * ```
+ * // an unsigned transaction
+ * CardanoCrypto.Tx.estimateFee(config, tx);
+ * // two inputs and the outputs of the transaction
+ * CardanoCrypto.Tx.estimateFee(config, { inputs: 2, outputs: [{ address, value }] });
+ * ```
+ *
+ * @param module - the WASM module that is used for crypto operations
+ * @param config - the configuration, with the optional linear fee
+ *                 coefficients (see `Config.withLinearFee`)
+ * @param tx     - the transaction (encoded in cbor) or its skeleton:
+ *                 { inputs, outputs, witnesses } (at most 10000 inputs,
+ *                 the witnesses are 'PkWitness' or 'RedeemWitness',
+ *                 'PkWitness' by default)
+ * @returns {*}  - { fee, size }
+ */
+export const estimateFee = (module, config, tx) => {
+  const input_tx = (tx instanceof Uint8Array || Array.isArray(tx))
+    ? { tx: Array.from(tx) }
+    : { skeleton: tx };
+  return callResult(module, module.xtx_estimate_fee, { config, tx: input_tx });
 };
 
+/**
+ * Build an unsigned transaction, to be signed offline with
+ * `Wallet.signEnvelope`. No private key is needed: the inputs (see
+ * `Wallet.spend`) give the addressing of their keys in the wallet.
+ *
+ * @param module            - the WASM module that is used for crypto operations
+ * @param config            - the configuration
+ * @param inputs            - the list of inputs
+ * @param outputs           - the list of payment to make
+ * @param change_addr       - the address to send the change to
+ * @param change_addressing - the addressing of the change address in the wallet
+ * @param policy            - the input selection (see `Wallet.spend`),
+ *                            'first_in_first_out' by default
+ * @returns {*}             - { failed, result }, the result being the envelope:
+ *                            { config, tx, inputs, change, fee }
+ */
+export const prepare = (module, config, inputs, outputs, change_addr, change_addressing, policy) => (
+  call(module, module.xtx_prepare,
+    { config, inputs, outputs, change_addr, change_addressing, selection_policy: selectionPolicy(policy) })
+);
+
+// the script of a script witness: { version, script }
+const scriptInput = ({ version, script }) => ({ version, script: Array.from(script) });
+
+/**
+ * Assemble the signed transaction from the transaction and the witnesses of
+ * its inputs: signatures made elsewhere, the validator and redeemer scripts
+ * of the inputs spending script addresses or, for the addresses of
+ * `HdWallet.multisigAddress`, the validator and the signatures (`Tx.sign`)
+ * with the index of their key.
+ *
+ * ```js
+ * CardanoCrypto.Tx.assemble(tx, [
+ *   { pk: { public_key: xpub, signature } },
+ *   { script: { validator: { version: 0, script: validator }, redeemer: { version: 0, script: redeemer } } },
+ *   { multisig: { validator, signatures: [{ index: 0, signature: sig0 }, { index: 2, signature: sig2 }] } },
+ * ]);
+ * ```
+ *
+ * @param module    - the WASM module that is used for crypto operations
+ * @param tx        - the transaction (encoded in cbor)
+ * @param witnesses - the witnesses, in the order of the inputs
+ * @returns {*}     - { failed, result: { id, cbor_encoded_tx } }
+ */
+export const assemble = (module, tx, witnesses) => (
+  call(module, module.xtx_assemble, {
+    tx: Array.from(tx),
+    witnesses: witnesses.map((witness) => {
+      if (witness.pk) {
+        return { pk: { public_key: Array.from(witness.pk.public_key), signature: Array.from(witness.pk.signature) } };
+      }
+      if (witness.multisig) {
+        return { multisig: {
+          validator: scriptInput(witness.multisig.validator),
+          signatures: witness.multisig.signatures.map(({ index, signature }) => ({ index, signature: Array.from(signature) }))
+        } };
+      }
+      return { script: { validator: scriptInput(witness.script.validator), redeemer: scriptInput(witness.script.redeemer) } };
+    })
+  })
+);
+
+// the redemption keys (32 bytes) are printed in base64 on the ADA vending
+// certificates
+const redemptionKey = (key) => {
+  const key_array = typeof key === 'string' ? Buffer.from(key, 'base64') : key;
+  if (key_array.length !== 32) {
+    throw new Error('invalid redemption key: ' + key_array.length + ' bytes instead of 32');
+  }
+  return key_array;
+};
+
+/**
+ * Compute the redeem address of an ADA vending certificate.
+ *
+ * @param module - the WASM module that is used for crypto operations
+ * @param key    - the redemption private key (32 bytes), in base64 or as
+ *                 an array of bytes
+ * @returns {*}  - the redeem address (encoded in cbor)
+ */
+export const redeemAddress = (module, key) => {
+  const key_array = redemptionKey(key);
+  const bufkey  = newArray(module, key_array);
+  const bufaddr = newOutput(module);
+
+  let rs = module.wallet_redeem_to_address(bufkey, key_array.length, bufaddr);
+  let addr = copyOutput(module, bufaddr, rs);
+
+  module.dealloc_secret(bufkey, key_array.length);
+
+  checkStatus(module, rs);
+  return addr;
+};
+
+/**
+ * Claim the value of an ADA vending certificate: build and sign the
+ * transaction sending the whole value of the redeem address to the
+ * destination (the redemption transactions are exempt from fees).
+ *
+ * @param module      - the WASM module that is used for crypto operations
+ * @param config      - the configuration
+ * @param key         - the redemption private key, in base64 or as an array of bytes
+ * @param value       - the value of the certificate (in lovelace, a safe
+ *                      integer or a string of decimal digits)
+ * @param destination - the address receiving the value
+ * @returns {*}       - { failed, result: { id, cbor_encoded_tx } }
+ */
+export const redeem = (module, config, key, value, destination) => (
+  callSecret(module, module.xtx_redeem,
+    { config, redemption_key: Array.from(redemptionKey(key)), value: amountString(value), destination })
+);
+
 export default {
   newTxOut: apply(newTxOut, RustModule),
   newTxIn:  apply(newTxIn, RustModule),
@@ -178,4 +385,12 @@ export default {
   addOutput: apply(addOutput, RustModule),
   sign: apply(sign, RustModule),
   verify: apply(verify, RustModule),
+  decode: apply(decode, RustModule),
+  signInputs: apply(signInputs, RustModule),
+  verifyAll: apply(verifyAll, RustModule),
+  estimateFee: apply(estimateFee, RustModule),
+  prepare: apply(prepare, RustModule),
+  redeemAddress: apply(redeemAddress, RustModule),
+  redeem: apply(redeem, RustModule),
+  assemble: apply(assemble, RustModule),
 };
@@ -1,35 +1,35 @@
-import iconv from 'iconv-lite';
 import RustModule from './RustModule';
-import { newArray, newArray0, copyArray } from './utils/arrays';
 import { apply } from './utils/functions';
 import { base16 } from './utils/strings';
-
-const MAX_OUTPUT_SIZE = 4096;
+import { call, callSecret, callSecretResult } from './utils/jrpc';
+import { DEFAULT_SCHEME } from './utils/scheme';
+import { selectionPolicy } from './utils/selection';
 
 /**
  * Create a wallet object from the given seed.
  *
  * @param module - the WASM module that is used for crypto operations
  * @param seed   - the 32 bytes seed to generate the wallet from
+ * @param scheme - the derivation scheme ('V1' or 'V2', default 'V2'),
+ *                 recorded in the wallet object
  * @returns {*}  - a wallet object (JSON object)
  */
-export const fromSeed = (module, seed) => {
-    const input_str = JSON.stringify(seed);
-    const input_array = iconv.encode(input_str, 'utf8');
-
-    const bufinput  = newArray(module, input_array);
-    const bufoutput = newArray0(module, MAX_OUTPUT_SIZE);
-
-    let rsz = module.xwallet_create(bufinput, input_array.length, bufoutput);
-    let output_array = copyArray(module, bufoutput, rsz);
-
-    module.dealloc(bufoutput);
-    module.dealloc(bufinput);
-
-    let output_str = iconv.decode(Buffer.from(output_array), 'utf8');
-    return JSON.parse(output_str);
-};
+export const fromSeed = (module, seed, scheme = DEFAULT_SCHEME) => (
+    callSecret(module, module.xwallet_create, { seed: seed, derivation_scheme: scheme })
+);
 
+/**
+ * Create a wallet object from the given mnemonic phrase.
+ *
+ * @param module   - the WASM module that is used for crypto operations
+ * @param mnemonic - the mnemonic phrase (string)
+ * @param language - the language of the word list (default 'english')
+ * @param scheme   - the derivation scheme ('V1' or 'V2', default 'V2')
+ * @returns {*}    - a wallet object (JSON object)
+ */
+export const fromMnemonic = (module, mnemonic, language = 'english', scheme = DEFAULT_SCHEME) => (
+    callSecret(module, module.xwallet_from_mnemonic, { mnemonic, language, derivation_scheme: scheme })
+);
 
 /**
  * Create an account, for public key derivation (using bip44 model).
@@ -45,25 +45,55 @@ export const fromSeed = (module, seed) => {
  * @param account - the account number (0 to (0x80000000 - 1)).
  * @returns {*}  - a list of ready to use addresses
  */
-export const newAccount = (module, wallet, account, type, indices) => {
-    const input = { wallet: wallet
-                  , account: account
-                  };
-    const input_str = JSON.stringify(input);
-    const input_array = iconv.encode(input_str, 'utf8');
-
-    const bufinput  = newArray(module, input_array);
-    const bufoutput = newArray0(module, MAX_OUTPUT_SIZE);
+export const newAccount = (module, wallet, account) => (
+    callSecret(module, module.xwallet_account, { wallet: wallet, account: account })
+);
 
-    let rsz = module.xwallet_account(bufinput, input_array.length, bufoutput);
-    let output_array = copyArray(module, bufoutput, rsz);
+/**
+ * Create a watch-only account from the account public key, exported once
+ * from the signing device (the key at `m/44'/1815'/account'`). The account
+ * generates the addresses without any private key.
+ *
+ * @param module - the WASM module that is used for crypto operations
+ * @param xpub   - the account public key (64 bytes)
+ * @param scheme - the derivation scheme of the wallet ('V1' or 'V2', default 'V2')
+ * @returns {*}  - { failed, result }, the result being the account
+ */
+export const accountFromXPub = (module, xpub, scheme = DEFAULT_SCHEME) => (
+    call(module, module.xwallet_account_from_xpub, { xpub: Array.from(xpub), derivation_scheme: scheme })
+);
 
-    module.dealloc(bufoutput);
-    module.dealloc(bufinput);
+/**
+ * Find, among the given addresses, the ones of the account.
+ *
+ * @param module    - the WASM module that is used for crypto operations
+ * @param account   - the account
+ * @param addresses - the addresses to check
+ * @param limit     - the number of addresses searched in each chain (1000 by default, at most 100000)
+ * @param scheme    - the derivation scheme of the wallet ('V1' or 'V2', default 'V2')
+ * @returns {*}     - { failed, result }, the result being the list of the
+ *                    addresses of the account: { address, address_type, index }
+ */
+export const recogniseAddresses = (module, account, addresses, limit = 1000, scheme = DEFAULT_SCHEME) => (
+    call(module, module.xwallet_account_recognise, { account, derivation_scheme: scheme, addresses, limit })
+);
 
-    let output_str = iconv.decode(Buffer.from(output_array), 'utf8');
-    return JSON.parse(output_str);
-};
+/**
+ * Find the used addresses of the account: the external and internal chains
+ * are walked until `gap_limit` consecutive addresses are not used.
+ *
+ * @param module    - the WASM module that is used for crypto operations
+ * @param account   - the account
+ * @param used      - the addresses known to be used (with transactions)
+ * @param gap_limit - the number of unused addresses ending a chain (20 by default, at most 10000)
+ * @param scheme    - the derivation scheme of the wallet ('V1' or 'V2', default 'V2')
+ * @returns {*}     - { failed, result }, the result being, for the `external`
+ *                    and `internal` chains, the used addresses ({ address, index })
+ *                    and the next fresh index: { used, next_index }
+ */
+export const discoverAddresses = (module, account, used, gap_limit = 20, scheme = DEFAULT_SCHEME) => (
+    call(module, module.xwallet_account_discover, { account, derivation_scheme: scheme, used, gap_limit })
+);
 
 /**
  * Generate addresses for the given wallet.
@@ -79,28 +109,13 @@ export const newAccount = (module, wallet, account, type, indices) => {
  * @param account - the account as create by `CardanoCrypto.Wallet.newAccount`
  * @param type    - the addresses type ("Internal" or "External")
  * @param indices - the addresse indices
+ * @param scheme  - the derivation scheme the wallet was created with
+ *                  ('V1' or 'V2', default 'V2')
  * @returns {*}  - a list of ready to use addresses
  */
-export const generateAddresses = (module, account, type, indices) => {
-    const input = { account, account
-                  , address_type: type
-                  , indices: indices
-                  };
-    const input_str = JSON.stringify(input);
-    const input_array = iconv.encode(input_str, 'utf8');
-
-    const bufinput  = newArray(module, input_array);
-    const bufoutput = newArray0(module, MAX_OUTPUT_SIZE);
-
-    let rsz = module.xwallet_addresses(bufinput, input_array.length, bufoutput);
-    let output_array = copyArray(module, bufoutput, rsz);
-
-    module.dealloc(bufoutput);
-    module.dealloc(bufinput);
-
-    let output_str = iconv.decode(Buffer.from(output_array), 'utf8');
-    return JSON.parse(output_str);
-};
+export const generateAddresses = (module, account, type, indices, scheme = DEFAULT_SCHEME) => (
+    call(module, module.xwallet_addresses, { account, derivation_scheme: scheme, address_type: type, indices })
+);
 
 /**
  * Generate a ready to send, signed, transaction.
@@ -140,6 +155,10 @@ export const generateAddresses = (module, account, type, indices) => {
  * console.log("details of the transaction: ", result.tx);
  * console.log("fees of the transaction: ", result.fee);
  * console.log("bytes array (encoded tx): ", result.cbor_encoded_tx);
+ * console.log("spent inputs: ", result.inputs);
+ *
+ * // selecting the largest inputs first
+ * result = CardanoCrypto.Wallet.spend(wallet, inputs, outputs, change_addr, 'largest_first').result;
  * ```
  *
  * @param module - the WASM module that is used for crypto operations
@@ -147,30 +166,67 @@ export const generateAddresses = (module, account, type, indices) => {
  * @param inputs - the list of inputs
  * @param outputs - the list of payment to make
  * @param change_addr - the address to send the change to
- * @returns {*}  - a ready to use, signed transaction encoded in cbor, the fee computed and the JSON encoded version of the TxAux.
+ * @param policy - the input selection: 'largest_first', 'first_in_first_out',
+ *                 'random_improve' or 'exact_match' (no change output), the
+ *                 default selection of the wallet if not given
+ * @param linear_fee - the linear fee coefficients ({ a, b }, only used with
+ *                     a selection policy), the ones of the network by default.
+ *                     The transaction is signed with the configuration of the wallet.
+ * @returns {*}  - a ready to use, signed transaction encoded in cbor, the fee computed, the spent inputs and the JSON encoded version of the TxAux.
  */
-export const spend = (module, wallet, inputs, outputs, change_addr) => {
-    const input = { wallet: wallet
-                  , inputs: inputs
-                  , outputs: outputs
-                  , change_addr: change_addr
-                  };
-    const input_str = JSON.stringify(input);
-    const input_array = iconv.encode(input_str, 'utf8');
-
-    const bufinput  = newArray(module, input_array);
-    const bufoutput = newArray0(module, MAX_OUTPUT_SIZE);
-
-    let rsz = module.xwallet_spend(bufinput, input_array.length, bufoutput);
-    let output_array = copyArray(module, bufoutput, rsz);
-
-    module.dealloc(bufoutput);
-    module.dealloc(bufinput);
-
-    let output_str = iconv.decode(Buffer.from(output_array), 'utf8');
-    return JSON.parse(output_str);
-};
+export const spend = (module, wallet, inputs, outputs, change_addr, policy, linear_fee) => (
+    callSecret(module, module.xwallet_spend,
+               { wallet, inputs, outputs, change_addr, selection_policy: selectionPolicy(policy), linear_fee })
+);
+
 
+/**
+ * Send all the given inputs to one address, without change: the fee is
+ * taken from the sent value.
+ *
+ * @param module      - the WASM module that is used for crypto operations
+ * @param wallet      - The wallet object as created by the `fromSeed` function
+ * @param inputs      - the list of inputs (see `spend`)
+ * @param destination - the address to send the funds to
+ * @param linear_fee  - the linear fee coefficients ({ a, b }), the ones of the
+ *                      network by default. The transaction is signed with the
+ *                      configuration of the wallet.
+ * @returns {*}       - the same as `spend`, fails if the inputs don't cover the fee
+ */
+export const sweep = (module, wallet, inputs, destination, linear_fee) => (
+    callSecret(module, module.xwallet_sweep, { wallet, inputs, destination, linear_fee })
+);
+
+/**
+ * Check and sign the unsigned transaction built by `Tx.prepare`: the
+ * envelope must be for the protocol magic of the wallet, the inputs and
+ * the change must be addresses of the wallet and the fee must be the one
+ * of the transaction. The transaction is signed with the wallet config.
+ *
+ * @param module   - the WASM module that is used for crypto operations
+ * @param wallet   - The wallet object as created by the `fromSeed` function
+ * @param envelope - the unsigned transaction envelope
+ * @returns {*}    - { failed, result: { id, cbor_encoded_tx } }, the signed
+ *                   transaction (TxAux) ready to be sent
+ */
+export const signEnvelope = (module, wallet, envelope) => (
+    callSecret(module, module.xtx_sign_envelope, { wallet, envelope })
+);
+
+/**
+ * Decode the given address and return its details.
+ *
+ * @param module  - the WASM module that is used for crypto operations
+ * @param address - the address, in base58 (a string) or in CBOR (an array of bytes)
+ * @returns {*}   - { failed, result }, the result being the address details:
+ *                  { address, root, address_type, spending_data,
+ *                    stake_distribution, network_magic, payload }
+ *                  or { failed, msg } with the reason the address is invalid
+ */
+export const inspectAddress = (module, address) => (
+    call(module, module.xaddress_inspect,
+         typeof address === 'string' ? { base58: address } : { cbor: Array.from(address) })
+);
 
 /**
  * Check if the given hexadecimal string is a valid Cardano Extended Address
@@ -179,27 +235,54 @@ export const spend = (module, wallet, inputs, outputs, change_addr) => {
  * @param address - the hexadecimal address to check
  * @returns {*}  - true or false
  */
-export const checkAddress = (module, address) => {
-    const input_str = JSON.stringify(address);
-    const input_array = iconv.encode(input_str, 'utf8');
+export const checkAddress = (module, address) => (
+    call(module, module.xwallet_checkaddress, address)
+);
 
-    const bufinput  = newArray(module, input_array);
-    const bufoutput = newArray0(module, MAX_OUTPUT_SIZE);
-
-    let rsz = module.xwallet_checkaddress(bufinput, input_array.length, bufoutput);
-    let output_array = copyArray(module, bufoutput, rsz);
-
-    module.dealloc(bufoutput);
-    module.dealloc(bufinput);
+/**
+ * Find, among the given addresses and unspent outputs, the ones of the
+ * legacy Daedalus wallet of the given mnemonic phrase.
+ *
+ * @param module    - the WASM module that is used for crypto operations
+ * @param mnemonic  - the 12 words of the Daedalus wallet
+ * @param addresses - the addresses to check
+ * @param outputs   - the unspent outputs ({ address, value }) to check
+ * @param language  - the language of the word list (default 'english')
+ * @returns {*}     - the addresses and outputs of the wallet with their
+ *                    derivation path ({ account, index })
+ */
+export const daedalusRestore = (module, mnemonic, addresses, outputs = [], language = 'english') => (
+    callSecret(module, module.xwallet_daedalus_restore, { mnemonic, language, addresses, outputs })
+);
 
-    let output_str = iconv.decode(Buffer.from(output_array), 'utf8');
-    return JSON.parse(output_str);
-};
+/**
+ * Get the root private key of the legacy Daedalus wallet of the given
+ * mnemonic phrase. The keys of the addresses found by `daedalusRestore`
+ * are derived from it with the 'V1' scheme at the hardened path
+ * `[account, index]`.
+ *
+ * @param module   - the WASM module that is used for crypto operations
+ * @param mnemonic - the 12 words of the Daedalus wallet
+ * @param language - the language of the word list (default 'english')
+ * @returns {*}    - the root extended private key (96 bytes)
+ */
+export const daedalusRootKey = (module, mnemonic, language = 'english') => (
+  new Uint8Array(callSecretResult(module, module.xwallet_daedalus_root_key, { mnemonic, language }))
+);
 
 export default {
   fromSeed: apply(fromSeed, RustModule),
+  fromMnemonic: apply(fromMnemonic, RustModule),
   newAccount: apply(newAccount, RustModule),
   generateAddresses: apply(generateAddresses, RustModule),
+  accountFromXPub: apply(accountFromXPub, RustModule),
+  recogniseAddresses: apply(recogniseAddresses, RustModule),
+  discoverAddresses: apply(discoverAddresses, RustModule),
   spend: apply(spend, RustModule),
+  sweep: apply(sweep, RustModule),
+  signEnvelope: apply(signEnvelope, RustModule),
   checkAddress: apply(checkAddress, RustModule),
+  inspectAddress: apply(inspectAddress, RustModule),
+  daedalusRestore: apply(daedalusRestore, RustModule),
+  daedalusRootKey: apply(daedalusRootKey, RustModule),
 };
@@ -6,6 +6,10 @@ import Payload from './Payload.js';
 import Tx from './Tx.js';
 import Config from './Config.js';
 import Wallet from './Wallet.js';
+import Handle from './Handle.js';
+import Pbkdf2 from './Pbkdf2.js';
+import Bip39 from './Bip39.js';
+import { V1, V2 } from './utils/scheme';
 
 module.exports = {
   Payload,
@@ -17,4 +21,8 @@ module.exports = {
   Tx,
   Wallet,
   Config,
+  Handle,
+  Pbkdf2,
+  Bip39,
+  DerivationScheme: { V1, V2 },
 };
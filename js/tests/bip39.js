@@ -0,0 +1,46 @@
+const expect = require('chai').expect;
+const CardanoCrypto = require('../../dist/index.js');
+
+const hex = (str) => new Uint8Array(Buffer.from(str, 'hex'));
+
+const TEST_VECTORS = [
+  {
+    entropy: '00000000000000000000000000000000',
+    mnemonic: 'abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about',
+    seed: 'c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04'
+  },
+  {
+    entropy: '7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f',
+    mnemonic: 'legal winner thank year wave sausage worth useful legal winner thank yellow',
+    seed: '2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607'
+  }
+];
+
+describe('Bip39', function() {
+    TEST_VECTORS.forEach(({ entropy, mnemonic, seed }, i) => {
+        describe('Test ' + i, function() {
+            it('entropy to mnemonic', function() {
+                expect(CardanoCrypto.Bip39.entropyToMnenomic(hex(entropy))).equal(mnemonic);
+            });
+            it('mnemonic to entropy', function() {
+                expect(new Uint8Array(CardanoCrypto.Bip39.mnemonicToEntropy(mnemonic))).deep.equal(hex(entropy));
+            });
+            it('mnemonic to seed', function() {
+                expect(CardanoCrypto.Bip39.mnemonicToSeed(mnemonic, 'TREZOR')).deep.equal(hex(seed));
+            });
+        });
+    });
+
+    it('reject an invalid checksum', function() {
+        const mnemonic = 'abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon';
+        expect(CardanoCrypto.Bip39.validateMnemonic(mnemonic)).equal(false);
+        expect(() => CardanoCrypto.Bip39.mnemonicToEntropy(mnemonic)).to.throw();
+    });
+
+    it('create a wallet from a mnemonic', function() {
+        const mnemonic = TEST_VECTORS[0].mnemonic;
+        const seed = CardanoCrypto.Bip39.mnemonicToWalletSeed(mnemonic);
+        expect(CardanoCrypto.Wallet.fromMnemonic(mnemonic))
+            .deep.equal(CardanoCrypto.Wallet.fromSeed(seed));
+    });
+});
@@ -0,0 +1,58 @@
+const expect = require('chai').expect;
+const CardanoCrypto = require('../../dist/index.js');
+
+const SEED = [ 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08
+             , 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x10
+             , 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18
+             , 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f, 0x20
+             ];
+
+describe('Derivation paths', function() {
+    const HdWallet = CardanoCrypto.HdWallet;
+    const root = HdWallet.fromSeed(SEED);
+
+    it('derive a textual path as successive derivations', function() {
+        const expected = HdWallet.derivePrivate(
+            HdWallet.derivePrivate(HdWallet.derivePrivate(root, 0x8000002C), 0x80000717), 2);
+        expect(HdWallet.derivePrivatePath(root, "m/44'/1815'/2")).to.deep.equal(expected);
+        expect(HdWallet.derivePrivatePath(root, [0x8000002C, 0x80000717, 2])).to.deep.equal(expected);
+    });
+
+    it('derive a range of public keys', function() {
+        const xpub = HdWallet.toPublic(root);
+        const keys = HdWallet.derivePublicRange(xpub, 10, 5);
+        expect(keys.length).equal(5);
+        expect(keys[3]).to.deep.equal(HdWallet.derivePublic(xpub, 13));
+        expect(HdWallet.derivePublicPath(xpub, "m/0/13")).to.deep.equal(
+            HdWallet.derivePublic(HdWallet.derivePublic(xpub, 0), 13));
+    });
+
+    it('reject too large ranges', function() {
+        const xpub = HdWallet.toPublic(root);
+        expect(() => HdWallet.derivePublicRange(xpub, 0, 0x7fffffff)).to.throw(/invalid argument `count`/);
+    });
+
+    it('reject invalid paths', function() {
+        const xpub = HdWallet.toPublic(root);
+        expect(() => HdWallet.derivePrivatePath(root, "m/44'//0")).to.throw(/invalid argument `path`/);
+        expect(() => HdWallet.derivePublicPath(xpub, "m/0'/1")).to.throw(/invalid argument `path`/);
+    });
+});
+
+describe('Derivation schemes', function() {
+    const HdWallet = CardanoCrypto.HdWallet;
+    const root = HdWallet.fromSeed(SEED);
+
+    it('derive different keys with the V1 and V2 schemes', function() {
+        expect(HdWallet.derivePrivate(root, 0x80000000, 'V1'))
+            .not.to.deep.equal(HdWallet.derivePrivate(root, 0x80000000, 'V2'));
+    });
+
+    it('record the scheme in the wallet', function() {
+        const wallet  = CardanoCrypto.Wallet.fromSeed(SEED, 'V1').result;
+        const account = CardanoCrypto.Wallet.newAccount(wallet, 0).result;
+        expect(wallet.derivation_scheme).equal('V1');
+        expect(CardanoCrypto.Wallet.generateAddresses(account, "External", [0], 'V1').failed).equal(false);
+        expect(CardanoCrypto.Wallet.generateAddresses(account, "External", [0], 'V2').failed).equal(true);
+    });
+});
@@ -0,0 +1,21 @@
+const expect = require('chai').expect;
+const CardanoCrypto = require('../../dist/index.js');
+
+describe('Error reporting', function() {
+    it('report an invalid address instead of aborting', function() {
+        expect(() => CardanoCrypto.Tx.newTxOut(new Uint8Array([0x01, 0x02, 0x03]), 42))
+            .to.throw(/invalid argument `address`/);
+    });
+
+    it('report an invalid transaction instead of aborting', function() {
+        const txin = CardanoCrypto.Tx.newTxIn(new Uint8Array(32), 0);
+        expect(() => CardanoCrypto.Tx.addInput(new Uint8Array([0xff]), txin))
+            .to.throw(/invalid argument `tx`/);
+    });
+
+    it('keep working after an error', function() {
+        expect(() => CardanoCrypto.Tx.addInput(new Uint8Array([0xff]), new Uint8Array([0xff])))
+            .to.throw();
+        expect(CardanoCrypto.Tx.create().length).to.be.above(0);
+    });
+});
@@ -0,0 +1,28 @@
+const expect = require('chai').expect;
+const CardanoCrypto = require('../../dist/index.js');
+
+const MEGABYTE = 1024 * 1024;
+
+describe('Memory', function() {
+    const memorySize = () => CardanoCrypto.RustModule.memory.buffer.byteLength;
+
+    it('release the inputs of the raw functions', function() {
+        const message = new Uint8Array(MEGABYTE);
+        CardanoCrypto.Blake2b.blake2b_256(message);
+        const before = memorySize();
+        for (let i = 0; i < 64; i++) {
+            CardanoCrypto.Blake2b.blake2b_256(message);
+        }
+        expect(memorySize() - before).to.be.below(16 * MEGABYTE);
+    });
+
+    it('release the inputs of the JSON functions', function() {
+        const address = 'x'.repeat(MEGABYTE);
+        CardanoCrypto.Wallet.checkAddress(address);
+        const before = memorySize();
+        for (let i = 0; i < 64; i++) {
+            CardanoCrypto.Wallet.checkAddress(address);
+        }
+        expect(memorySize() - before).to.be.below(16 * MEGABYTE);
+    });
+});
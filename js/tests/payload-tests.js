@@ -29,6 +29,15 @@ let mkTest = (i) => {
             expect(CardanoCrypto.Payload.decrypt_derivation_path(key, payload))
                 .deep.equal(derivation_path);
         });
+
+        it('decrypt the payloads of a list of addresses', function() {
+            const address = CardanoCrypto.HdWallet.publicKeyToAddress(pubkey, payload);
+            const other = CardanoCrypto.HdWallet.publicKeyToAddress(pubkey, new Uint8Array(payload.length));
+            expect(CardanoCrypto.Payload.decrypt_addresses(key, [other, address]))
+                .deep.equal([{ index: 1, path: Array.from(derivation_path) }]);
+            expect(CardanoCrypto.Payload.decrypt_addresses(key, [new Uint8Array([1, 2, 3]), address]))
+                .deep.equal([{ index: 1, path: Array.from(derivation_path) }]);
+        });
     });
 }
 
@@ -0,0 +1,22 @@
+const expect = require('chai').expect;
+const CardanoCrypto = require('../../dist/index.js');
+
+const hex = (str) => new Uint8Array(Buffer.from(str, 'hex'));
+const ascii = (str) => new Uint8Array(Buffer.from(str, 'ascii'));
+
+describe('PBKDF2', function() {
+    it('derive a key with HMAC-SHA256', function() {
+        expect(CardanoCrypto.Pbkdf2.sha256(ascii('password'), ascii('salt'), 1, 32))
+            .deep.equal(hex('120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b'));
+    });
+
+    it('derive a key with HMAC-SHA512', function() {
+        expect(CardanoCrypto.Pbkdf2.sha512(ascii('password'), ascii('salt'), 1, 64))
+            .deep.equal(hex('867f70cf1ade02cff3752599a3a53dc4af34c7a669815ae5d513554e1c8cf252c02d470a285a0501bad999bfe943c08f050235d7d68b1da55e63f73b60a57fce'));
+    });
+
+    it('accept passwords and salts with nul bytes', function() {
+        expect(CardanoCrypto.Pbkdf2.sha256(new Uint8Array([0, 1, 0]), new Uint8Array([0]), 1, 64).length)
+            .equal(64);
+    });
+});
@@ -53,6 +53,125 @@ let mkTest = (i) => {
             expect(CardanoCrypto.Tx.verify(cfg, tx, xpub, signature))
                 .equal(true);
         });
+
+        it('decode a Tx', function() {
+            const decoded = CardanoCrypto.Tx.decode(tx);
+            expect(decoded.type).equal('Tx');
+            expect(decoded.inputs).deep.equal([{ id: Buffer.from(txid).toString('hex'), index: index }]);
+            expect(decoded.outputs.length).equal(1);
+            expect(decoded.outputs[0].value).equal(String(amount));
+            expect(decoded.attributes).equal('a0');
+            expect(decoded.witnesses).equal(null);
+            expect(() => CardanoCrypto.Tx.decode(new Uint8Array([0xff]))).to.throw(/TxAux.*Tx.*TxIn.*TxOut/);
+        });
+
+        it('create a TxOut with a 64 bits amount', function() {
+            const max = '45000000000000000';
+            expect(CardanoCrypto.Tx.decode(CardanoCrypto.Tx.newTxOut(address, max)).value).equal(max);
+            expect(() => CardanoCrypto.Tx.newTxOut(address, '45000000000000001'))
+                .to.throw(/invalid argument `amount`/);
+        });
+
+        it('sign all the inputs of a Tx', function() {
+            const signed = CardanoCrypto.Tx.signInputs(cfg, tx, [xprv]);
+            expect(signed.failed).equal(false);
+            const decoded = CardanoCrypto.Tx.decode(signed.result.cbor_encoded_tx);
+            expect(decoded.type).equal('TxAux');
+            expect(decoded.witnesses).deep.equal([{
+                type: 'PkWitness',
+                public_key: Buffer.from(xpub).toString('hex'),
+                signature: Buffer.from(signature).toString('hex')
+            }]);
+            expect(CardanoCrypto.Tx.signInputs(cfg, tx, []).failed).equal(true);
+        });
+
+        it('report the violations of a signed Tx', function() {
+            const txaux = CardanoCrypto.Tx.signInputs(cfg, tx, [xprv]).result.cbor_encoded_tx;
+            const spent = CardanoCrypto.Tx.decode(txout);
+            const violations = CardanoCrypto.Tx.verifyAll(cfg, txaux, [{ address: spent.address, value: amount }])
+                .map(({ violation }) => violation);
+            expect(violations).to.include('insufficient_fee');
+            expect(violations).not.to.include('invalid_signature');
+        });
+
+        it('estimate the fee of a Tx', function() {
+            const estimated = CardanoCrypto.Tx.estimateFee(cfg, tx);
+            expect(estimated.size).to.be.above(tx.length);
+            expect(estimated.size)
+                .equal(CardanoCrypto.Tx.signInputs(cfg, tx, [xprv]).result.cbor_encoded_tx.length);
+            const cheap = CardanoCrypto.Tx.estimateFee(CardanoCrypto.Config.withLinearFee(cfg, 1000, 1), tx);
+            expect(cheap.fee).equal(1000 + cheap.size);
+            expect(() => CardanoCrypto.Tx.estimateFee(cfg, { inputs: 4000000000, outputs: [] }))
+                .to.throw(/invalid argument `inputs`/);
+            expect(() => CardanoCrypto.Tx.estimateFee(CardanoCrypto.Config.withLinearFee(cfg, -1, 1), tx))
+                .to.throw(/invalid linear fee coefficients/);
+        });
+
+        it('redeem an ADA vending certificate', function() {
+            const key = Buffer.from(new Uint8Array(32).fill(7)).toString('base64');
+            const redeem_address = CardanoCrypto.Tx.redeemAddress(key);
+            expect(CardanoCrypto.Wallet.inspectAddress(redeem_address).result.address_type).equal('redeem');
+            expect(CardanoCrypto.Wallet.inspectAddress(redeem_address).result.spending_data).equal('redeem_public_key');
+            const redeemed = CardanoCrypto.Tx.redeem(cfg, key, 1000000, address);
+            expect(redeemed.failed).equal(false);
+            const decoded = CardanoCrypto.Tx.decode(redeemed.result.cbor_encoded_tx);
+            expect(decoded.witnesses[0].type).equal('RedeemWitness');
+            expect(decoded.outputs[0].value).equal('1000000');
+            const violations = CardanoCrypto.Tx.verifyAll(cfg, redeemed.result.cbor_encoded_tx,
+                [{ address: CardanoCrypto.HdWallet.addressToBase58(redeem_address), value: 1000000 }]);
+            expect(violations).deep.equal([]);
+            expect(CardanoCrypto.Tx.redeem(cfg, key, '45000000000000000', address).failed).equal(false);
+            expect(() => CardanoCrypto.Tx.redeemAddress(new Uint8Array(16))).to.throw(/32/);
+        });
+
+        it('assemble a Tx with a script witness', function() {
+            const validator = { version: 0, script: new Uint8Array([1, 2, 3]) };
+            const redeemer  = { version: 0, script: new Uint8Array([4, 5]) };
+            const script_address = CardanoCrypto.HdWallet.scriptToAddress(validator.version, validator.script);
+            expect(CardanoCrypto.Wallet.inspectAddress(script_address).result.address_type).equal('script');
+            const assembled = CardanoCrypto.Tx.assemble(tx, [{ script: { validator, redeemer } }]);
+            expect(assembled.failed).equal(false);
+            expect(CardanoCrypto.Tx.decode(assembled.result.cbor_encoded_tx).witnesses[0].type).equal('ScriptWitness');
+            const violations = (spent) => CardanoCrypto.Tx.verifyAll(cfg, assembled.result.cbor_encoded_tx,
+                [{ address: CardanoCrypto.HdWallet.addressToBase58(spent), value: 10000000 }]).map(({ violation }) => violation);
+            expect(violations(script_address)).deep.equal([]);
+            expect(violations(address)).deep.equal(['witness_address_mismatch']);
+            const signed = CardanoCrypto.Tx.assemble(tx, [{ pk: { public_key: xpub, signature } }]);
+            expect(signed.result.cbor_encoded_tx)
+                .deep.equal(CardanoCrypto.Tx.signInputs(cfg, tx, [xprv]).result.cbor_encoded_tx);
+        });
+
+        it('spend from a 2-of-3 multisig address', function() {
+            const xprvs = [1, 2, 3].map(i => CardanoCrypto.HdWallet.fromSeed(new Uint8Array(32).fill(i)));
+            const xpubs = xprvs.map(key => CardanoCrypto.HdWallet.toPublic(key));
+            const { address: multisig_address, validator } = CardanoCrypto.HdWallet.multisigAddress(2, xpubs);
+            expect(CardanoCrypto.Wallet.inspectAddress(multisig_address).result.address_type).equal('script');
+            expect(() => CardanoCrypto.HdWallet.multisigAddress(4, xpubs)).to.throw();
+            const sign = (i) => ({ index: i, signature: CardanoCrypto.Tx.sign(cfg, tx, xprvs[i]) });
+            const violations = (signatures) => {
+                const assembled = CardanoCrypto.Tx.assemble(tx, [{ multisig: { validator, signatures } }]);
+                expect(assembled.failed).equal(false);
+                return CardanoCrypto.Tx.verifyAll(cfg, assembled.result.cbor_encoded_tx,
+                    [{ address: CardanoCrypto.HdWallet.addressToBase58(multisig_address), value: 10000000 }])
+                    .map(({ violation }) => violation);
+            };
+            expect(violations([sign(0), sign(2)])).deep.equal([]);
+            expect(violations([sign(1)])).deep.equal(['script_rejected']);
+            expect(violations([sign(1), sign(1)])).deep.equal(['script_rejected']);
+            expect(violations([sign(0), { index: 1, signature }])).deep.equal(['script_rejected']);
+        });
+
+        it('build and sign a Tx with handles', function() {
+            const htx   = CardanoCrypto.Handle.txNew();
+            const hxprv = CardanoCrypto.Handle.xprvNew(xprv);
+            CardanoCrypto.Handle.txAddInput(htx, txid, index);
+            CardanoCrypto.Handle.txAddOutput(htx, address, amount);
+            expect(CardanoCrypto.Handle.txToCbor(htx)).deep.equal(tx);
+            expect(CardanoCrypto.Handle.txSign(cfg, hxprv, htx)).deep.equal(signature);
+            CardanoCrypto.Handle.xprvFree(hxprv);
+            CardanoCrypto.Handle.txFree(htx);
+            expect(() => CardanoCrypto.Handle.txFree(htx)).to.throw(/invalid argument `handle`/);
+        });
     });
 }
 
@@ -23,6 +23,43 @@ let mkTest = (i) => {
             expect(CardanoCrypto.HdWallet.addressGetPayload(address))
                 .deep.equal(payload);
         });
+
+        it('check the address belongs to the public key', function() {
+            expect(CardanoCrypto.HdWallet.addressIsOf(pubkey, address)).equal(true);
+            const other = CardanoCrypto.HdWallet.toPublic(CardanoCrypto.HdWallet.fromSeed(new Uint8Array(32)));
+            expect(CardanoCrypto.HdWallet.addressIsOf(other, address)).equal(false);
+        });
+
+        it('base58 round trip', function() {
+            const base58 = CardanoCrypto.HdWallet.addressToBase58(address);
+            expect(base58).to.match(/^DdzFF/);
+            expect(CardanoCrypto.HdWallet.addressFromBase58(base58))
+                .deep.equal(address);
+        });
+
+        it('inspect the address', function() {
+            const base58 = CardanoCrypto.HdWallet.addressToBase58(address);
+            const inspected = CardanoCrypto.Wallet.inspectAddress(base58);
+            expect(inspected.failed).equal(false);
+            expect(inspected.result.address).equal(base58);
+            expect(inspected.result.address_type).equal('pubkey');
+            expect(inspected.result.stake_distribution.type).equal('bootstrap_era');
+            expect(inspected.result.payload.length).equal(payload.length);
+            expect(CardanoCrypto.Wallet.inspectAddress(address).result).deep.equal(inspected.result);
+        });
+
+        it('report why an address is invalid', function() {
+            const inspected = CardanoCrypto.Wallet.inspectAddress(address.slice(0, -1));
+            expect(inspected.failed).equal(true);
+            expect(inspected.msg).to.be.a('string');
+        });
+
+        it('base58 with an invalid checksum', function() {
+            const base58 = CardanoCrypto.HdWallet.addressToBase58(address);
+            const last = base58[base58.length - 1] === '1' ? '2' : '1';
+            expect(() => CardanoCrypto.HdWallet.addressFromBase58(base58.slice(0, -1) + last))
+                .to.throw();
+        });
     });
 }
 
@@ -0,0 +1,208 @@
+const expect = require('chai').expect;
+const CardanoCrypto = require('../../dist/index.js');
+
+const SEED = [ 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08
+             , 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x10
+             , 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18
+             , 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f, 0x20
+             ];
+
+describe('Wallet', function() {
+    const wallet  = CardanoCrypto.Wallet.fromSeed(SEED).result;
+    const account = CardanoCrypto.Wallet.newAccount(wallet, 0).result;
+
+    it('generate a few hundred addresses', function() {
+        let indices = [];
+        for (let i = 0; i < 300; i++) { indices.push(i); }
+        const addresses = CardanoCrypto.Wallet.generateAddresses(account, "External", indices);
+        expect(addresses.failed).equal(false);
+        expect(addresses.result.length).equal(300);
+    });
+
+    it('do not recognise the addresses of another wallet as Daedalus ones', function() {
+        const mnemonic = 'abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about';
+        const addresses = CardanoCrypto.Wallet.generateAddresses(account, "External", [0, 1, 2]).result;
+        const restored = CardanoCrypto.Wallet.daedalusRestore(mnemonic, addresses);
+        expect(restored.failed).equal(false);
+        expect(restored.result.addresses.length).equal(0);
+    });
+
+    it('recognise the address of a Daedalus wallet and its derivation path', function() {
+        const mnemonic = 'abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about';
+        const path = [0x80000000, 0x80000005];
+        const root = CardanoCrypto.Wallet.daedalusRootKey(mnemonic);
+        const xpub = CardanoCrypto.HdWallet.toPublic(CardanoCrypto.HdWallet.derivePrivatePath(root, path, 'V1'));
+        const hdkey = CardanoCrypto.Payload.initialise(CardanoCrypto.HdWallet.toPublic(root));
+        const payload = CardanoCrypto.Payload.encrypt_derivation_path(hdkey, path);
+        const address = CardanoCrypto.HdWallet.addressToBase58(CardanoCrypto.HdWallet.publicKeyToAddress(xpub, payload));
+        const others = CardanoCrypto.Wallet.generateAddresses(account, "External", [0, 1]).result;
+
+        const restored = CardanoCrypto.Wallet.daedalusRestore(mnemonic, others.concat([address]));
+        expect(restored.failed).equal(false);
+        expect(restored.result.addresses).deep.equal([ { address, path: { account: path[0], index: path[1] } } ]);
+    });
+
+    it('only restore Daedalus wallets from 12 words', function() {
+        const mnemonic = 'abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon address';
+        const restored = CardanoCrypto.Wallet.daedalusRestore(mnemonic, []);
+        expect(restored.failed).equal(true);
+        expect(restored.msg).to.match(/12 words/);
+        expect(() => CardanoCrypto.Wallet.daedalusRootKey(mnemonic)).to.throw(/12 words/);
+    });
+
+    it('spend the largest inputs first', function() {
+        const addresses = CardanoCrypto.Wallet.generateAddresses(account, "External", [0, 1]).result;
+        const input = (i, value) => (
+            { ptr: { index: i, id: "1c7b178c1655628ca87c7da6a5d9d13c1e0a304094ac88770768d565e3d20e0b" }
+            , value: { address: addresses[0], value: value }
+            , addressing: { account: 0, change: 0, index: 0 }
+            });
+        const inputs = [ input(0, 1000000), input(1, 5000000), input(2, 2000000) ];
+        const outputs = [ { address: addresses[1], value: 3000000 } ];
+        const spent = CardanoCrypto.Wallet.spend(wallet, inputs, outputs, addresses[0], 'largest_first');
+        expect(spent.failed).equal(false);
+        expect(spent.result.inputs.map((input) => input.ptr.index)).deep.equal([1]);
+    });
+
+    describe('input selection', function() {
+        const addresses = CardanoCrypto.Wallet.generateAddresses(account, "External", [0, 1]).result;
+        const input = (i, value) => (
+            { ptr: { index: i, id: "1c7b178c1655628ca87c7da6a5d9d13c1e0a304094ac88770768d565e3d20e0b" }
+            , value: { address: addresses[0], value: value }
+            , addressing: { account: 0, change: 0, index: 0 }
+            });
+        const inputs = [ input(0, 1000000), input(1, 5000000), input(2, 2000000), input(3, 3000000) ];
+        const spentIndices = (spent) => spent.result.inputs.map((input) => input.ptr.index);
+
+        it('spend the inputs in order', function() {
+            const outputs = [ { address: addresses[1], value: 3000000 } ];
+            const spent = CardanoCrypto.Wallet.spend(wallet, inputs, outputs, addresses[0], 'first_in_first_out');
+            expect(spent.failed).equal(false);
+            expect(spentIndices(spent)).deep.equal([0, 1]);
+        });
+
+        it('find the inputs paying exactly the outputs', function() {
+            const outputs = [ { address: addresses[1], value: 5000000 } ];
+            const spent = CardanoCrypto.Wallet.spend(wallet, inputs, outputs, addresses[0], 'exact_match', { a: 0, b: 0 });
+            expect(spent.failed).equal(false);
+            expect(spentIndices(spent).reduce((sum, i) => sum + inputs[i].value.value, 0)).equal(5000000);
+            expect(CardanoCrypto.Tx.decode(spent.result.cbor_encoded_tx).outputs.length).equal(1);
+        });
+
+        it('select the same random inputs with the same seed', function() {
+            const outputs = [ { address: addresses[1], value: 2500000 } ];
+            const policy = { random_improve: { seed: 42 } };
+            const first = CardanoCrypto.Wallet.spend(wallet, inputs, outputs, addresses[0], policy);
+            const second = CardanoCrypto.Wallet.spend(wallet, inputs, outputs, addresses[0], policy);
+            expect(first.failed).equal(false);
+            expect(spentIndices(first)).deep.equal(spentIndices(second));
+        });
+
+        it('select random inputs paying the outputs and the fee', function() {
+            const outputs = [ { address: addresses[1], value: 2500000 } ];
+            const spent = CardanoCrypto.Wallet.spend(wallet, inputs, outputs, addresses[0], { random_improve: { seed: 7 } });
+            expect(spent.failed).equal(false);
+            const resolved = spent.result.inputs.map((input) => input.value);
+            expect(CardanoCrypto.Tx.verifyAll(wallet.config, spent.result.cbor_encoded_tx, resolved)).deep.equal([]);
+            const paid = CardanoCrypto.Tx.decode(spent.result.cbor_encoded_tx).outputs
+                .reduce((sum, output) => sum + Number(output.value), 0);
+            const total = resolved.reduce((sum, value) => sum + value.value, 0);
+            expect(paid).to.be.at.least(2500000);
+            expect(total).to.be.above(paid);
+        });
+    });
+
+    it('sign with the protocol magic of the wallet', function() {
+        const testnet = Object.assign({}, wallet, { config: { protocol_magic: 633343913 } });
+        const addresses = CardanoCrypto.Wallet.generateAddresses(account, "External", [0, 1]).result;
+        const inputs = [
+            { ptr: { index: 0, id: "1c7b178c1655628ca87c7da6a5d9d13c1e0a304094ac88770768d565e3d20e0b" }
+            , value: { address: addresses[0], value: 5000000 }
+            , addressing: { account: 0, change: 0, index: 0 }
+            }
+        ];
+        const outputs = [ { address: addresses[1], value: 3000000 } ];
+        const spent = CardanoCrypto.Wallet.spend(testnet, inputs, outputs, addresses[0], 'largest_first');
+        expect(spent.failed).equal(false);
+        const resolved = inputs.map((input) => input.value);
+        const violations = (config) => CardanoCrypto.Tx.verifyAll(config, spent.result.cbor_encoded_tx, resolved)
+            .map(({ violation }) => violation);
+        expect(violations(testnet.config)).deep.equal([]);
+        expect(violations(CardanoCrypto.Config.defaultConfig())).to.include('invalid_signature');
+    });
+
+    it('sweep the inputs without change', function() {
+        const addresses = CardanoCrypto.Wallet.generateAddresses(account, "External", [0, 1]).result;
+        const input = (i, value) => (
+            { ptr: { index: i, id: "1c7b178c1655628ca87c7da6a5d9d13c1e0a304094ac88770768d565e3d20e0b" }
+            , value: { address: addresses[0], value: value }
+            , addressing: { account: 0, change: 0, index: 0 }
+            });
+        const swept = CardanoCrypto.Wallet.sweep(wallet, [ input(0, 1000000), input(1, 2000000) ], addresses[1]);
+        expect(swept.failed).equal(false);
+        const decoded = CardanoCrypto.Tx.decode(swept.result.cbor_encoded_tx);
+        expect(decoded.outputs.length).equal(1);
+        expect(CardanoCrypto.Wallet.sweep(wallet, [ input(0, 10) ], addresses[1]).failed).equal(true);
+    });
+
+    it('sweep with the protocol magic of the wallet', function() {
+        const testnet = Object.assign({}, wallet, { config: { protocol_magic: 633343913 } });
+        const addresses = CardanoCrypto.Wallet.generateAddresses(account, "External", [0, 1]).result;
+        const inputs = [
+            { ptr: { index: 0, id: "1c7b178c1655628ca87c7da6a5d9d13c1e0a304094ac88770768d565e3d20e0b" }
+            , value: { address: addresses[0], value: 2000000 }
+            , addressing: { account: 0, change: 0, index: 0 }
+            }
+        ];
+        const swept = CardanoCrypto.Wallet.sweep(testnet, inputs, addresses[1]);
+        expect(swept.failed).equal(false);
+        expect(CardanoCrypto.Tx.verifyAll(testnet.config, swept.result.cbor_encoded_tx, inputs.map((input) => input.value)))
+            .deep.equal([]);
+    });
+
+    it('prepare a transaction online and sign it offline', function() {
+        const cfg = CardanoCrypto.Config.defaultConfig();
+        const addresses = CardanoCrypto.Wallet.generateAddresses(account, "External", [0, 1]).result;
+        const inputs =
+            [ { ptr: { index: 0, id: "1c7b178c1655628ca87c7da6a5d9d13c1e0a304094ac88770768d565e3d20e0b" }
+              , value: { address: addresses[0], value: 5000000 }
+              , addressing: { account: 0, change: 0, index: 0 }
+              }
+            ];
+        const outputs = [ { address: addresses[1], value: 1000000 } ];
+        const change_addressing = { account: 0, change: 0, index: 1 };
+        const envelope = CardanoCrypto.Tx.prepare(cfg, inputs, outputs, addresses[1], change_addressing).result;
+        const signed = CardanoCrypto.Wallet.signEnvelope(wallet, envelope);
+        expect(signed.failed).equal(false);
+        expect(CardanoCrypto.Tx.decode(signed.result.cbor_encoded_tx).type).equal('TxAux');
+
+        const tampered = Object.assign({}, envelope, { fee: envelope.fee - 1 });
+        expect(CardanoCrypto.Wallet.signEnvelope(wallet, tampered).failed).equal(true);
+
+        const other_network = Object.assign({}, envelope, { config: { protocol_magic: 633343913 } });
+        expect(CardanoCrypto.Wallet.signEnvelope(wallet, other_network).failed).equal(true);
+    });
+
+    it('generate the addresses of a watch-only account', function() {
+        const root = CardanoCrypto.HdWallet.fromSeed(SEED);
+        const xpub = CardanoCrypto.HdWallet.toPublic(
+            CardanoCrypto.HdWallet.derivePrivatePath(root, "m/44'/1815'/0'"));
+        const watching = CardanoCrypto.Wallet.accountFromXPub(xpub).result;
+        const addresses = CardanoCrypto.Wallet.generateAddresses(watching, "Internal", [3, 7]).result;
+        const recognised = CardanoCrypto.Wallet.recogniseAddresses(account, addresses, 10).result;
+        expect(recognised.map(({ address_type, index }) => [address_type, index]))
+            .deep.equal([["Internal", 3], ["Internal", 7]]);
+    });
+
+    it('discover the used addresses up to the gap limit', function() {
+        const external = CardanoCrypto.Wallet.generateAddresses(account, "External", [2, 15, 40]).result;
+        const discovered = CardanoCrypto.Wallet.discoverAddresses(account, external, 20).result;
+        expect(discovered.external.used.map(({ index }) => index)).deep.equal([2, 15]);
+        expect(discovered.external.next_index).equal(16);
+        expect(discovered.internal.next_index).equal(0);
+    });
+
+    it('reject too large gap limits', function() {
+        expect(CardanoCrypto.Wallet.discoverAddresses(account, [], 0xffffffff).failed).equal(true);
+    });
+});
@@ -0,0 +1,44 @@
+const TWO_POW_32 = 0x100000000;
+
+/**
+ * Check the amount of lovelace is a safe integer or a string of decimal
+ * digits and return it as a string of decimal digits, as expected by the
+ * JSON functions of the WASM module.
+ *
+ * @param amount - a safe integer or a string of decimal digits
+ * @returns {*}  - the amount as a string
+ */
+export const amountString = (amount) => {
+  if (typeof amount === 'number') {
+    if (!Number.isSafeInteger(amount) || amount < 0) {
+      throw new Error('invalid amount `' + amount + '`, use a string of decimal digits');
+    }
+    return String(amount);
+  }
+  if (typeof amount !== 'string' || !/^[0-9]+$/.test(amount)) {
+    throw new Error('invalid amount `' + amount + '`');
+  }
+  return amount;
+};
+
+/**
+ * Split an amount of lovelace in its low and high 32 bits, as expected
+ * by the raw functions of the WASM module.
+ *
+ * @param amount - a safe integer or a string of decimal digits
+ * @returns {*}  - [low, high]
+ */
+export const splitAmount = (amount) => {
+  const digits = amountString(amount);
+  let lo = 0;
+  let hi = 0;
+  for (let i = 0; i < digits.length; i++) {
+    lo = lo * 10 + (digits.charCodeAt(i) - 48);
+    hi = hi * 10 + Math.floor(lo / TWO_POW_32);
+    lo = lo % TWO_POW_32;
+    if (hi >= TWO_POW_32) {
+      throw new Error('invalid amount `' + amount + '`: does not fit in 64 bits');
+    }
+  }
+  return [lo, hi];
+};
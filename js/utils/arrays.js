@@ -66,3 +66,61 @@ export const copyArrayU32 = (module, ptr, sz) => {
 
     return new Uint32Array(collect());
 };
+
+// size (in bytes) of the slot where the WASM module writes the address of
+// the output buffer it allocated
+const OUTPUT_SLOT_SIZE = 4;
+
+// allocate the slot where the WASM module writes the address of the
+// output buffer it allocated (see `copyOutput`)
+export const newOutput = (module) => {
+    return newArray0(module, OUTPUT_SLOT_SIZE);
+};
+
+// read the address of the output buffer from the given slot
+const outputPtr = (module, slot) => {
+    let memory = new Uint8Array(module.memory.buffer);
+    return ((memory[slot + 0] <<  0)
+          | (memory[slot + 1] <<  8)
+          | (memory[slot + 2] << 16)
+          | (memory[slot + 3] << 24)) >>> 0;
+};
+
+// copy the output buffer of `sz` bytes allocated by the WASM module then
+// release it and the slot. If `sz` is a negative status code nothing was
+// allocated and null is returned.
+export const copyOutput = (module, slot, sz) => {
+    let result = null;
+    if (sz >= 0) {
+        const ptr = outputPtr(module, slot);
+        result = copyArray(module, ptr, sz);
+        module.dealloc_output(ptr, sz);
+    }
+    module.dealloc(slot, OUTPUT_SLOT_SIZE);
+    return result;
+};
+
+// same as `copyOutput` for an output holding private keys: the output is
+// wiped before being released
+export const copyOutputSecret = (module, slot, sz) => {
+    let result = null;
+    if (sz >= 0) {
+        const ptr = outputPtr(module, slot);
+        result = copyArray(module, ptr, sz);
+        module.dealloc_output_secret(ptr, sz);
+    }
+    module.dealloc(slot, OUTPUT_SLOT_SIZE);
+    return result;
+};
+
+// same as `copyOutput` for an output of 32bit integers (`sz` in bytes)
+export const copyOutputU32 = (module, slot, sz) => {
+    let result = null;
+    if (sz >= 0) {
+        const ptr = outputPtr(module, slot);
+        result = copyArrayU32(module, ptr, sz / 4);
+        module.dealloc_output(ptr, sz);
+    }
+    module.dealloc(slot, OUTPUT_SLOT_SIZE);
+    return result;
+};
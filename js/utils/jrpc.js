@@ -0,0 +1,69 @@
+import iconv from 'iconv-lite';
+import { newArray, newOutput, copyOutput, copyOutputSecret } from './arrays';
+import { checkStatus } from './status';
+
+const invoke = (module, fn, input, secret) => {
+  const input_array = iconv.encode(JSON.stringify(input), 'utf8');
+
+  const bufinput  = newArray(module, input_array);
+  const bufoutput = newOutput(module);
+
+  let rsz = fn(bufinput, input_array.length, bufoutput);
+  let output_array = secret
+    ? copyOutputSecret(module, bufoutput, rsz)
+    : copyOutput(module, bufoutput, rsz);
+
+  if (secret) {
+    module.dealloc_secret(bufinput, input_array.length);
+    input_array.fill(0);
+  } else {
+    module.dealloc(bufinput, input_array.length);
+  }
+  // no output was returned (e.g. too large for the module memory)
+  checkStatus(module, rsz);
+
+  let output_str = iconv.decode(Buffer.from(output_array), 'utf8');
+  if (secret) {
+    output_array.fill(0);
+  }
+  return JSON.parse(output_str);
+};
+
+/**
+ * Call one of the JSON functions (`x*`) of the WASM module.
+ *
+ * @param module - the WASM module that is used for crypto operations
+ * @param fn     - the function of the module to call
+ * @param input  - the input object (serialised in JSON)
+ * @returns {*}  - the output object ({ failed, result } or { failed, loc, msg }),
+ *                 throws an Error if the module could not return any output
+ */
+export const call = (module, fn, input) => invoke(module, fn, input, false);
+
+/**
+ * Same as `call` for the inputs or the outputs containing private keys,
+ * seeds or mnemonic phrases (e.g. the wallet objects): the copies of the
+ * input and of the output in the module memory are wiped after the call.
+ */
+export const callSecret = (module, fn, input) => invoke(module, fn, input, true);
+
+const result = (output) => {
+  if (output.failed) {
+    const err = new Error(output.msg);
+    err.loc = output.loc;
+    throw err;
+  }
+  return output.result;
+};
+
+/**
+ * Same as `call` but returns the result directly and throw an Error
+ * if the call failed.
+ */
+export const callResult = (module, fn, input) => result(call(module, fn, input));
+
+/**
+ * Same as `callSecret` but returns the result directly and throw an Error
+ * if the call failed.
+ */
+export const callSecretResult = (module, fn, input) => result(callSecret(module, fn, input));
@@ -0,0 +1,14 @@
+// the key derivation schemes: `V1` is the scheme of the legacy Daedalus
+// wallets, `V2` the one of the current wallets
+export const V1 = 'V1';
+export const V2 = 'V2';
+export const DEFAULT_SCHEME = V2;
+
+// the value of the derivation scheme expected by the raw functions
+export const schemeCode = (scheme) => {
+  switch (scheme) {
+    case V1: return 1;
+    case V2: return 2;
+    default: throw new Error('unknown derivation scheme `' + scheme + '`');
+  }
+};
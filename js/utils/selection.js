@@ -0,0 +1,17 @@
+// the input selection policies of `Wallet.spend`
+export const LARGEST_FIRST = 'largest_first';
+export const FIRST_IN_FIRST_OUT = 'first_in_first_out';
+export const RANDOM_IMPROVE = 'random_improve';
+export const EXACT_MATCH = 'exact_match';
+
+// the selection policy as expected by the WASM module: the random-improve
+// policy takes the seed of its random generator
+export const selectionPolicy = (policy) => {
+  if (policy === undefined || policy === null) {
+    return null;
+  }
+  if (policy === RANDOM_IMPROVE) {
+    return { random_improve: { seed: Math.floor(Math.random() * 0xFFFFFFFF) } };
+  }
+  return policy;
+};
@@ -0,0 +1,55 @@
+import iconv from 'iconv-lite';
+import { newOutput, copyOutput } from './arrays';
+
+// status codes returned by the raw `wallet_*` functions of the WASM module.
+//
+// There is no status for a panic: the module is built for
+// `wasm32-unknown-unknown` where a panic cannot be caught, it traps the
+// whole instance (a `WebAssembly.RuntimeError` is thrown) and the module
+// must not be used anymore; load a new instance instead.
+export const SUCCESS = 0;
+export const FAILED = -1;
+export const INVALID_ARGUMENT = -2;
+
+/**
+ * Retrieve the details of the error of the last raw function call.
+ *
+ * @param module - the WASM module that is used for crypto operations
+ * @returns {*} - null or the jrpc error object ({ failed, loc, msg, argument })
+ */
+export const lastError = (module) => {
+  const buferror = newOutput(module);
+
+  let rsz = module.wallet_last_error(buferror);
+  let error_array = copyOutput(module, buferror, rsz);
+  if (rsz <= 0) {
+    return null;
+  }
+  return JSON.parse(iconv.decode(Buffer.from(error_array), 'utf8'));
+};
+
+/**
+ * Throw an Error with the details of the last error if the given
+ * status code is negative, otherwise returns the status code.
+ *
+ * @param module - the WASM module that is used for crypto operations
+ * @param status - the value returned by the raw function
+ * @returns {*} - the given status
+ */
+export const checkStatus = (module, status) => {
+  if (status >= 0) {
+    return status;
+  }
+  const error = lastError(module);
+  if (error === null) {
+    throw new Error('unknown error (status ' + status + ')');
+  }
+  const message = error.argument
+    ? 'invalid argument `' + error.argument + '`: ' + error.msg
+    : error.msg;
+  const err = new Error(message);
+  err.status = status;
+  err.argument = error.argument;
+  err.loc = error.loc;
+  throw err;
+};
@@ -6,7 +6,7 @@ extern crate rcw;
 extern crate wallet_crypto;
 
 use self::rcw::hmac::{Hmac};
-use self::rcw::sha2::{Sha256};
+use self::rcw::sha2::{Sha256, Sha512};
 use self::rcw::pbkdf2::{pbkdf2};
 use self::rcw::blake2b::{Blake2b};
 use self::rcw::digest::{Digest};
@@ -15,19 +15,26 @@ use self::wallet_crypto::hdwallet;
 use self::wallet_crypto::paperwallet;
 use self::wallet_crypto::address;
 use self::wallet_crypto::hdpayload;
-use self::wallet_crypto::{util::{hex}, tx, coin, hash::{HASH_SIZE}};
+use self::wallet_crypto::redeem;
+use self::wallet_crypto::{util::{hex, base58}, tx, coin, hash::{HASH_SIZE}};
+use self::wallet_crypto::tx::fee::{FeeAlgorithm};
 use self::wallet_crypto::config::{Config};
 use self::wallet_crypto::wallet;
 use self::wallet_crypto::wallet::{Wallet, Account};
 use self::wallet_crypto::bip44;
+use self::wallet_crypto::bip39;
 
 use self::wallet_crypto::cbor;
 use self::wallet_crypto::cbor::{encode_to_cbor, decode_from_cbor};
 
-use std::{mem, result, string, convert};
-use std::ffi::{CStr, CString};
+use serde::{Deserialize, Deserializer, de};
+
+use std::{mem, result, string, convert, i32};
+use std::ffi::{CString};
 use std::os::raw::{c_uint, c_uchar, c_char, c_void};
 use std::iter::repeat;
+use std::cell::{RefCell};
+use std::collections::{BTreeSet, BTreeMap};
 //use std::slice::{from_raw_parts};
 
 // In order to work with the memory we expose (de)allocation methods
@@ -53,356 +60,1126 @@ pub extern "C" fn dealloc_str(ptr: *mut c_char) {
     }
 }
 
+/// release an output buffer allocated by the module (see `return_data`)
+#[no_mangle]
+pub extern "C" fn dealloc_output(ptr: *mut c_uchar, sz: usize) {
+    unsafe {
+        let _buf : Box<[u8]> = Box::from_raw(std::slice::from_raw_parts_mut(ptr, sz));
+    }
+}
+
+/// same as `dealloc_output` but wipe the output before releasing it. To
+/// use for the outputs holding private keys (like the serialised wallets).
+#[no_mangle]
+pub extern "C" fn dealloc_output_secret(ptr: *mut c_uchar, sz: usize) {
+    unsafe {
+        secret::wipe(ptr, sz);
+        let _buf : Box<[u8]> = Box::from_raw(std::slice::from_raw_parts_mut(ptr, sz));
+    }
+}
+
+/// same as `dealloc` but wipe the memory before releasing it. To use for
+/// the buffers that held private keys, seeds, passwords or entropy.
 #[no_mangle]
-pub extern "C" fn pbkdf2_sha256(password: *mut c_char, salt: *mut c_char, iters: u32, output: u32) -> *mut c_char {
+pub extern "C" fn dealloc_secret(ptr: *mut c_uchar, cap: usize) {
     unsafe {
+        secret::wipe(ptr, cap);
+        let _buf = Vec::from_raw_parts(ptr, 0, cap);
+    }
+}
+
+/// Wiping of the secrets (private keys, seeds, passwords, entropy) from
+/// the wasm linear memory once they are not needed anymore.
+mod secret {
+    use std::{io, mem, ops, ptr, result};
+    use serde::{Serialize, Deserialize, Deserializer};
+    use serde_json;
+
+    /// overwrite the given memory with zeros, using volatile writes so
+    /// the compiler does not optimise them out
+    pub unsafe fn wipe(data: *mut u8, sz: usize) {
+        for i in 0..sz {
+            ptr::write_volatile(data.offset(i as isize), 0);
+        }
+    }
+
+    /// bytes wiped out of the memory when dropped
+    pub struct SecretBytes(Vec<u8>);
+    impl SecretBytes {
+        pub fn new(data: Vec<u8>) -> Self { SecretBytes(data) }
+
+        /// the bytes, not wiped anymore: the caller takes care of it
+        pub fn into_inner(mut self) -> Vec<u8> { mem::replace(&mut self.0, Vec::new()) }
+    }
+    impl ops::Deref for SecretBytes {
+        type Target = [u8];
+        fn deref(&self) -> &[u8] { &self.0[..] }
+    }
+    impl ops::DerefMut for SecretBytes {
+        fn deref_mut(&mut self) -> &mut [u8] { &mut self.0[..] }
+    }
+    impl Drop for SecretBytes {
+        fn drop(&mut self) {
+            let cap = self.0.capacity();
+            unsafe { wipe(self.0.as_mut_ptr(), cap) }
+        }
+    }
+
+    /// a string wiped out of the memory when dropped (mnemonic phrases,
+    /// passwords)
+    pub struct SecretString(String);
+    impl SecretString {
+        pub fn new(data: String) -> Self { SecretString(data) }
+    }
+    impl Default for SecretString {
+        fn default() -> Self { SecretString(String::new()) }
+    }
+    impl ops::Deref for SecretString {
+        type Target = str;
+        fn deref(&self) -> &str { &self.0 }
+    }
+    impl Drop for SecretString {
+        fn drop(&mut self) {
+            let cap = self.0.capacity();
+            unsafe { wipe(self.0.as_mut_vec().as_mut_ptr(), cap) }
+        }
+    }
+    impl<'de> Deserialize<'de> for SecretString {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> result::Result<Self, D::Error> {
+            String::deserialize(deserializer).map(SecretString)
+        }
+    }
+
+    /// a key or a seed wiped out of the memory when dropped.
+    ///
+    /// The value is dropped first then the memory of the value itself is
+    /// wiped, so it is never read once wiped (whatever its type, enums
+    /// included). The memory it may point to is not wiped: to use with
+    /// the types holding their secrets inline (like `XPrv` or `Seed`).
+    pub struct Secret<T>(mem::ManuallyDrop<T>);
+    impl<T> Secret<T> {
+        pub fn new(value: T) -> Self { Secret(mem::ManuallyDrop::new(value)) }
+    }
+    impl<T> ops::Deref for Secret<T> {
+        type Target = T;
+        fn deref(&self) -> &T { &self.0 }
+    }
+    impl<T> ops::DerefMut for Secret<T> {
+        fn deref_mut(&mut self) -> &mut T { &mut self.0 }
+    }
+    impl<T> Drop for Secret<T> {
+        fn drop(&mut self) {
+            unsafe {
+                mem::ManuallyDrop::drop(&mut self.0);
+                wipe(&mut self.0 as *mut mem::ManuallyDrop<T> as *mut u8, mem::size_of::<T>())
+            }
+        }
+    }
+    impl<'de, T: Deserialize<'de>> Deserialize<'de> for Secret<T> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> result::Result<Self, D::Error> {
+            T::deserialize(deserializer).map(Secret::new)
+        }
+    }
+
+    /// counts the bytes written, to size the buffer of `to_json`
+    struct Counter(usize);
+    impl io::Write for Counter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0 += buf.len();
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    }
+
+    /// serialise the value in JSON in a buffer allocated at its exact size
+    /// beforehand: the buffer is never reallocated, so no copy of the
+    /// secrets is left in the freed memory
+    pub fn to_json<T: Serialize>(value: &T) -> serde_json::Result<SecretBytes> {
+        let mut counter = Counter(0);
+        serde_json::to_writer(&mut counter, value)?;
+        let mut bytes = SecretBytes::new(Vec::with_capacity(counter.0));
+        serde_json::to_writer(&mut bytes.0, value)?;
+        Ok(bytes)
+    }
+}
+
+/// Status codes and error reporting of the raw `wallet_*` exports.
+///
+/// A raw export returns a non negative value on success (usually the
+/// number of bytes written in the output buffer) or one of the negative
+/// status codes defined here. The details of the last error (location,
+/// the faulty argument and the reason) are kept until the next call and
+/// can be retrieved, as a jrpc error object, with `wallet_last_error`.
+mod raw {
+    use std::cell::{RefCell};
+    use super::jrpc;
+
+    /// the function succeeded
+    pub const SUCCESS : i32 = 0;
+    /// the function did not fail but could not produce the expected
+    /// result (invalid signature, payload not decrypted...)
+    pub const FAILED : i32 = -1;
+    /// one of the argument is invalid (null pointer, malformed key or cbor...)
+    pub const INVALID_ARGUMENT : i32 = -2;
+
+    thread_local!(static LAST_ERROR: RefCell<Option<jrpc::Error>> = RefCell::new(None));
+
+    pub fn fail(status: i32, file: &str, line: u32, argument: Option<&str>, msg: String) -> i32 {
+        let mut error = jrpc::Error::new(format!("{} {}", file, line), msg);
+        error.argument = argument.map(|arg| arg.to_string());
+        LAST_ERROR.with(|last| *last.borrow_mut() = Some(error));
+        status
+    }
+
+    pub fn last_error() -> Option<jrpc::Error> {
+        LAST_ERROR.with(|last| last.borrow().clone())
+    }
+
+    /// clear the last error and run the given raw function.
+    ///
+    /// Panics cannot be caught on `wasm32-unknown-unknown` (the only panic
+    /// strategy is `abort`): a panic traps the whole module instance, so
+    /// the exports (raw and jrpc) must check their arguments and return an
+    /// error status instead.
+    pub fn call<F>(f: F) -> i32
+        where F: FnOnce() -> i32
+    {
+        LAST_ERROR.with(|last| *last.borrow_mut() = None);
+        f()
+    }
+}
 
-        let salt = CStr::from_ptr(salt);
-        let password = CStr::from_ptr(password);
+/// Entry point of raw error reporting
+macro_rules! raw_fail {
+    ($status:expr, $argument:expr, $msg:expr) => ({
+        raw::fail($status, file!(), line!(), $argument, $msg.to_string())
+    });
+    ($status:expr, $argument:expr, $fmt:expr, $($arg:tt)+) => ({
+        raw::fail($status, file!(), line!(), $argument, format!($fmt, $($arg)*))
+    });
+}
 
-        let salt = salt.to_bytes();
-        let password = password.to_bytes();
+/// unwrap the result or return `INVALID_ARGUMENT`, recording that the
+/// given argument is at fault.
+macro_rules! raw_try {
+    ($argument:expr, $expr:expr) => (match $expr {
+        Ok(val) => val,
+        Err(err) => { return raw_fail!(raw::INVALID_ARGUMENT, Some($argument), "{:?}", err); }
+    });
+    ($argument:expr, $expr:expr,) => (raw_try!($argument, $expr));
+}
 
-        let mut mac = Hmac::new(Sha256::new(), &password[..]);
-        let mut result: Vec<u8> = repeat(0).take(output as usize).collect();
-        pbkdf2(&mut mac, &salt[..], iters, &mut result);
-        let s = CString::new(result).unwrap();
-        s.into_raw()
+/// retrieve the details of the error returned by the last raw function
+/// call (as a jrpc error object). The output is empty if the last call
+/// did not fail.
+#[no_mangle]
+pub extern "C" fn wallet_last_error(out: *mut *mut c_uchar) -> i32 {
+    let output = match raw::last_error() {
+        None => Vec::new(),
+        Some(error) => match serde_json::to_vec(&error) {
+            Ok(output) => output,
+            Err(_) => return raw::FAILED
+        }
+    };
+    match unsafe { return_data(output, out) } {
+        Ok(sz) => sz,
+        Err(_) => raw::INVALID_ARGUMENT
     }
 }
 
-unsafe fn read_data(data_ptr: *const c_uchar, sz: usize) -> Vec<u8> {
+fn pbkdf2_hmac<D: Digest>(digest: D, password: &[u8], salt: &[u8], iters: u32, output: usize) -> secret::SecretBytes {
+    let mut mac = Hmac::new(digest, password);
+    let mut key = secret::SecretBytes::new(repeat(0).take(output).collect());
+    pbkdf2(&mut mac, salt, iters, &mut key);
+    key
+}
+
+/// derive `output` bytes from the password and the salt with
+/// PBKDF2-HMAC-SHA256 and write them in the `out` buffer
+#[no_mangle]
+pub extern "C" fn pbkdf2_sha256(password_ptr: *const c_uchar, password_sz: usize, salt_ptr: *const c_uchar, salt_sz: usize, iters: u32, output: u32, out: *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let password = raw_try!("password", unsafe { read_secret(password_ptr, password_sz) });
+        let salt = raw_try!("salt", unsafe { read_data(salt_ptr, salt_sz) });
+        let key = pbkdf2_hmac(Sha256::new(), &password, &salt, iters, output as usize);
+        raw_try!("out", unsafe { write_data(&key, out) });
+        raw::SUCCESS
+    })
+}
+
+/// derive `output` bytes from the password and the salt with
+/// PBKDF2-HMAC-SHA512 and write them in the `out` buffer
+#[no_mangle]
+pub extern "C" fn pbkdf2_sha512(password_ptr: *const c_uchar, password_sz: usize, salt_ptr: *const c_uchar, salt_sz: usize, iters: u32, output: u32, out: *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let password = raw_try!("password", unsafe { read_secret(password_ptr, password_sz) });
+        let salt = raw_try!("salt", unsafe { read_data(salt_ptr, salt_sz) });
+        let key = pbkdf2_hmac(Sha512::new(), &password, &salt, iters, output as usize);
+        raw_try!("out", unsafe { write_data(&key, out) });
+        raw::SUCCESS
+    })
+}
+
+unsafe fn read_data(data_ptr: *const c_uchar, sz: usize) -> Result<Vec<u8>> {
+        if data_ptr.is_null() { return Err(Error::ErrorNullPointer); }
         let data_slice = std::slice::from_raw_parts(data_ptr, sz);
         let mut data = Vec::with_capacity(sz);
         data.extend_from_slice(data_slice);
-        data
+        Ok(data)
 }
 
-unsafe fn write_data(data: &[u8], data_ptr: *mut c_uchar) {
+unsafe fn write_data(data: &[u8], data_ptr: *mut c_uchar) -> Result<()> {
+        if data_ptr.is_null() { return Err(Error::ErrorNullPointer); }
         let sz = data.len();
         let out = std::slice::from_raw_parts_mut(data_ptr, sz);
-        out[0..sz].clone_from_slice(data)
+        out[0..sz].clone_from_slice(data);
+        Ok(())
 }
 
-unsafe fn read_data_u32(data_ptr: *const c_uint, sz: usize) -> Vec<u32> {
+unsafe fn read_data_u32(data_ptr: *const c_uint, sz: usize) -> Result<Vec<u32>> {
+    if data_ptr.is_null() { return Err(Error::ErrorNullPointer); }
     let data_slice = std::slice::from_raw_parts(data_ptr, sz);
     let mut data = Vec::with_capacity(sz);
     data.extend_from_slice(data_slice);
-    data
+    Ok(data)
 }
 
-unsafe fn write_data_u32(data: &[u32], data_ptr: *mut c_uint) {
+/// hand the given bytes over to the caller: the output buffer is allocated
+/// by the module, its address is written at `out_ptr` and its length is
+/// returned. The caller owns the buffer and releases it with `dealloc_output`.
+unsafe fn return_data(data: Vec<u8>, out_ptr: *mut *mut c_uchar) -> Result<i32> {
+        if out_ptr.is_null() { return Err(Error::ErrorNullPointer); }
+        // the size is returned as a (positive) status code
+        if data.len() > i32::MAX as usize { return Err(Error::ErrorOutputTooLarge(data.len())); }
+        let mut data = data.into_boxed_slice();
         let sz = data.len();
-        let out = std::slice::from_raw_parts_mut(data_ptr, sz);
-        out[0..sz].clone_from_slice(data)
+        *out_ptr = data.as_mut_ptr();
+        mem::forget(data);
+        Ok(sz as i32)
+}
+
+/// same as `return_data` for an array of 32bit integers (in little endian,
+/// the byte order of the wasm memory). The returned length is in bytes.
+unsafe fn return_data_u32(data: &[u32], out_ptr: *mut *mut c_uchar) -> Result<i32> {
+        let mut bytes = Vec::with_capacity(data.len() * 4);
+        for v in data {
+            bytes.push((*v >>  0) as u8);
+            bytes.push((*v >>  8) as u8);
+            bytes.push((*v >> 16) as u8);
+            bytes.push((*v >> 24) as u8);
+        }
+        return_data(bytes, out_ptr)
+}
+
+/// same as `return_data` for the outputs holding secrets, released with
+/// `dealloc_output_secret`. The buffer of `secret::to_json` has the exact
+/// size of the data, so it is handed over without being reallocated, and
+/// it is wiped if it cannot be returned.
+unsafe fn return_secret(data: secret::SecretBytes, out_ptr: *mut *mut c_uchar) -> Result<i32> {
+        if out_ptr.is_null() { return Err(Error::ErrorNullPointer); }
+        if data.len() > i32::MAX as usize { return Err(Error::ErrorOutputTooLarge(data.len())); }
+        return_data(data.into_inner(), out_ptr)
 }
 
-unsafe fn read_xprv(xprv_ptr: *const c_uchar) -> hdwallet::XPrv {
+unsafe fn read_secret(data_ptr: *const c_uchar, sz: usize) -> Result<secret::SecretBytes> {
+        Ok(secret::SecretBytes::new(read_data(data_ptr, sz)?))
+}
+
+unsafe fn read_xprv(xprv_ptr: *const c_uchar) -> Result<secret::Secret<hdwallet::XPrv>> {
+        if xprv_ptr.is_null() { return Err(Error::ErrorNullPointer); }
         let xprv_slice = std::slice::from_raw_parts(xprv_ptr, hdwallet::XPRV_SIZE);
-        hdwallet::XPrv::from_slice(xprv_slice).unwrap()
+        Ok(secret::Secret::new(hdwallet::XPrv::from_slice(xprv_slice)?))
 }
 
-unsafe fn write_xprv(xprv: &hdwallet::XPrv, xprv_ptr: *mut c_uchar) {
+unsafe fn write_xprv(xprv: &hdwallet::XPrv, xprv_ptr: *mut c_uchar) -> Result<()> {
+        if xprv_ptr.is_null() { return Err(Error::ErrorNullPointer); }
         let out = std::slice::from_raw_parts_mut(xprv_ptr, hdwallet::XPRV_SIZE);
         out[0..hdwallet::XPRV_SIZE].clone_from_slice(xprv.as_ref());
+        Ok(())
 }
 
-unsafe fn read_xpub(xpub_ptr: *const c_uchar) -> hdwallet::XPub {
+unsafe fn read_xpub(xpub_ptr: *const c_uchar) -> Result<hdwallet::XPub> {
+        if xpub_ptr.is_null() { return Err(Error::ErrorNullPointer); }
         let xpub_slice = std::slice::from_raw_parts(xpub_ptr, hdwallet::XPUB_SIZE);
-        hdwallet::XPub::from_slice(xpub_slice).unwrap()
+        Ok(hdwallet::XPub::from_slice(xpub_slice)?)
 }
 
-unsafe fn write_xpub(xpub: &hdwallet::XPub, xpub_ptr: *mut c_uchar) {
+unsafe fn write_xpub(xpub: &hdwallet::XPub, xpub_ptr: *mut c_uchar) -> Result<()> {
+        if xpub_ptr.is_null() { return Err(Error::ErrorNullPointer); }
         let out = std::slice::from_raw_parts_mut(xpub_ptr, hdwallet::XPUB_SIZE);
         out[0..hdwallet::XPUB_SIZE].clone_from_slice(xpub.as_ref());
+        Ok(())
 }
 
-unsafe fn read_signature<T>(sig_ptr: *const c_uchar) -> hdwallet::Signature<T> {
+unsafe fn read_signature<T>(sig_ptr: *const c_uchar) -> Result<hdwallet::Signature<T>> {
+        if sig_ptr.is_null() { return Err(Error::ErrorNullPointer); }
         let signature_slice = std::slice::from_raw_parts(sig_ptr, hdwallet::SIGNATURE_SIZE);
-        hdwallet::Signature::from_slice(signature_slice).unwrap()
+        Ok(hdwallet::Signature::from_slice(signature_slice)?)
 }
 
-unsafe fn write_signature<T>(signature: &hdwallet::Signature<T>, out_ptr: *mut c_uchar) {
+unsafe fn write_signature<T>(signature: &hdwallet::Signature<T>, out_ptr: *mut c_uchar) -> Result<()> {
+        if out_ptr.is_null() { return Err(Error::ErrorNullPointer); }
         let out = std::slice::from_raw_parts_mut(out_ptr, hdwallet::SIGNATURE_SIZE);
         out[0..hdwallet::SIGNATURE_SIZE].clone_from_slice(signature.as_ref());
+        Ok(())
 }
 
-unsafe fn read_seed(seed_ptr: *const c_uchar) -> hdwallet::Seed {
+unsafe fn read_seed(seed_ptr: *const c_uchar) -> Result<secret::Secret<hdwallet::Seed>> {
+        if seed_ptr.is_null() { return Err(Error::ErrorNullPointer); }
         let seed_slice = std::slice::from_raw_parts(seed_ptr, hdwallet::SEED_SIZE);
-        hdwallet::Seed::from_slice(seed_slice).unwrap()
+        Ok(secret::Secret::new(hdwallet::Seed::from_slice(seed_slice)?))
+}
+
+unsafe fn read_hdkey(key_ptr: *const c_uchar) -> Result<hdpayload::HDKey> {
+        let key_bytes = read_data(key_ptr, hdpayload::HDKEY_SIZE)?;
+        hdpayload::HDKey::from_slice(&key_bytes).ok_or(Error::ErrorInvalidHDKey)
+}
+
+/// first hardened index of the BIP32 derivation
+const HARDENED : u32 = 0x80000000;
+
+/// parse a textual BIP32/BIP44 derivation path (`m/44'/1815'/0'/0/17`),
+/// the hardened indices being marked with `'`, `H` or `h`
+fn parse_derivation_path(path: &str) -> Result<Vec<u32>> {
+    let mut indices = Vec::new();
+    for (i, component) in path.trim().split('/').enumerate() {
+        if i == 0 && (component == "m" || component == "M") { continue; }
+        let (number, hardened) =
+            if component.ends_with('\'') || component.ends_with('H') || component.ends_with('h') {
+                (&component[..component.len() - 1], true)
+            } else {
+                (component, false)
+            };
+        let index : u32 = match number.parse() {
+            Ok(index) => index,
+            Err(_) => return Err(Error::ErrorDerivationPath(format!("invalid index `{}`", component)))
+        };
+        if index >= HARDENED {
+            return Err(Error::ErrorDerivationPath(format!("index `{}` out of range", component)));
+        }
+        indices.push(if hardened { index | HARDENED } else { index });
+    }
+    Ok(indices)
+}
+
+/// the derivation scheme given to the raw functions: `1` for the legacy
+/// Daedalus scheme (`V1`), `2` for the current scheme (`V2`)
+fn read_derivation_scheme(scheme: u32) -> Result<hdwallet::DerivationScheme> {
+    match scheme {
+        1 => Ok(hdwallet::DerivationScheme::V1),
+        2 => Ok(hdwallet::DerivationScheme::V2),
+        _ => Err(Error::ErrorDerivationScheme(scheme))
+    }
+}
+
+/// make sure an object created with the scheme `expected` is not used with
+/// another derivation scheme
+fn check_derivation_scheme(expected: hdwallet::DerivationScheme, given: hdwallet::DerivationScheme) -> Result<()> {
+    if expected == given { Ok(()) } else { Err(Error::ErrorDerivationSchemeMismatch(expected, given)) }
+}
+
+unsafe fn read_derivation_path(path_ptr: *const c_uchar, path_sz: usize) -> Result<Vec<u32>> {
+        let path = input_string_(path_ptr, path_sz)?;
+        parse_derivation_path(&path)
+}
+
+fn derive_private_path(xprv: &hdwallet::XPrv, scheme: hdwallet::DerivationScheme, path: &[u32]) -> secret::Secret<hdwallet::XPrv> {
+    let mut key = secret::Secret::new(xprv.clone());
+    for index in path {
+        key = secret::Secret::new(key.derive(scheme, *index));
+    }
+    key
+}
+
+fn derive_public_path(xpub: &hdwallet::XPub, scheme: hdwallet::DerivationScheme, path: &[u32]) -> Result<hdwallet::XPub> {
+    let mut key = xpub.clone();
+    for index in path {
+        key = key.derive(scheme, *index)?;
+    }
+    Ok(key)
+}
+
+/// the coefficients of the linear fee: `a + b * size` lovelace for a
+/// transaction of `size` bytes
+#[derive(Serialize, PartialEq, Clone, Copy, Debug)]
+struct LinearFeeCoefficients {
+    a: f64,
+    b: f64
+}
+impl<'de> Deserialize<'de> for LinearFeeCoefficients {
+    /// the coefficients must be finite and not negative
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Coefficients { a: f64, b: f64 }
+        let coefficients = Coefficients::deserialize(deserializer)?;
+        let valid = |c: f64| c.is_finite() && c >= 0.0;
+        if ! valid(coefficients.a) || ! valid(coefficients.b) {
+            return Err(de::Error::custom(format!("invalid linear fee coefficients: a = {}, b = {}", coefficients.a, coefficients.b)));
+        }
+        Ok(LinearFeeCoefficients { a: coefficients.a, b: coefficients.b })
+    }
+}
+
+/// the configuration of the functions building transactions: the
+/// `Config` fields and the optional linear fee coefficients (the ones of
+/// the network by default)
+#[derive(Serialize, Deserialize, Default, Debug)]
+struct TxConfig {
+    #[serde(flatten)]
+    config: Config,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    linear_fee: Option<LinearFeeCoefficients>
+}
+impl TxConfig {
+    fn linear_fee(&self) -> tx::fee::LinearFee {
+        match self.linear_fee {
+            None => tx::fee::LinearFee::default(),
+            Some(coefficients) => tx::fee::LinearFee::new(coefficients.a, coefficients.b)
+        }
+    }
+
+    /// the configuration of the wallet: the transactions of a wallet are
+    /// always signed with the protocol magic the wallet is configured with
+    fn of_wallet(wallet: &Wallet, linear_fee: Option<LinearFeeCoefficients>) -> Self {
+        TxConfig { config: wallet.config.clone(), linear_fee: linear_fee }
+    }
+}
+
+unsafe fn read_config(cfg_ptr: *const c_uchar, cfg_size: usize) -> Result<Config> {
+        let cfg_str = input_string_(cfg_ptr, cfg_size)?;
+        Ok(serde_json::from_str(cfg_str.as_str())?)
 }
 
 #[no_mangle]
-pub extern "C" fn wallet_from_seed(seed_ptr: *const c_uchar, out: *mut c_uchar) {
-    let seed = unsafe { read_seed(seed_ptr) };
-    let xprv = hdwallet::XPrv::generate_from_seed(&seed);
-    unsafe { write_xprv(&xprv, out) }
+pub extern "C" fn wallet_from_seed(seed_ptr: *const c_uchar, out: *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let seed = raw_try!("seed", unsafe { read_seed(seed_ptr) });
+        let xprv = secret::Secret::new(hdwallet::XPrv::generate_from_seed(&seed));
+        raw_try!("out", unsafe { write_xprv(&xprv, out) });
+        raw::SUCCESS
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn wallet_to_public(xprv_ptr: *const c_uchar, out: *mut c_uchar) {
-    let xprv = unsafe { read_xprv(xprv_ptr) };
-    let xpub = xprv.public();
-    unsafe { write_xpub(&xpub, out) }
+pub extern "C" fn wallet_to_public(xprv_ptr: *const c_uchar, out: *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let xprv = raw_try!("xprv", unsafe { read_xprv(xprv_ptr) });
+        let xpub = xprv.public();
+        raw_try!("out", unsafe { write_xpub(&xpub, out) });
+        raw::SUCCESS
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn wallet_derive_private(xprv_ptr: *const c_uchar, index: u32, out: *mut c_uchar) {
-    let xprv = unsafe { read_xprv(xprv_ptr) };
-    let child = xprv.derive(index);
-    unsafe { write_xprv(&child, out) }
+pub extern "C" fn wallet_derive_private(xprv_ptr: *const c_uchar, scheme: u32, index: u32, out: *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let xprv = raw_try!("xprv", unsafe { read_xprv(xprv_ptr) });
+        let scheme = raw_try!("scheme", read_derivation_scheme(scheme));
+        let child = secret::Secret::new(xprv.derive(scheme, index));
+        raw_try!("out", unsafe { write_xprv(&child, out) });
+        raw::SUCCESS
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn wallet_derive_public(xpub_ptr: *const c_uchar, index: u32, out: *mut c_uchar) -> bool {
-    let xpub = unsafe { read_xpub(xpub_ptr) };
-    match xpub.derive(index) {
-        Ok(child) => { unsafe { write_xpub(&child, out) }; true }
-        Err(_)    => { false }
-    }
+pub extern "C" fn wallet_derive_public(xpub_ptr: *const c_uchar, scheme: u32, index: u32, out: *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let xpub = raw_try!("xpub", unsafe { read_xpub(xpub_ptr) });
+        let scheme = raw_try!("scheme", read_derivation_scheme(scheme));
+        let child = raw_try!("index", xpub.derive(scheme, index));
+        raw_try!("out", unsafe { write_xpub(&child, out) });
+        raw::SUCCESS
+    })
+}
+
+/// derive the private key at the given textual derivation path
+#[no_mangle]
+pub extern "C" fn wallet_derive_private_path(xprv_ptr: *const c_uchar, scheme: u32, path_ptr: *const c_uchar, path_sz: usize, out: *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let xprv = raw_try!("xprv", unsafe { read_xprv(xprv_ptr) });
+        let scheme = raw_try!("scheme", read_derivation_scheme(scheme));
+        let path = raw_try!("path", unsafe { read_derivation_path(path_ptr, path_sz) });
+        let child = derive_private_path(&xprv, scheme, &path);
+        raw_try!("out", unsafe { write_xprv(&child, out) });
+        raw::SUCCESS
+    })
+}
+
+/// derive the private key at the given derivation path (array of indices)
+#[no_mangle]
+pub extern "C" fn wallet_derive_private_indices(xprv_ptr: *const c_uchar, scheme: u32, indices_ptr: *const c_uint, indices_sz: usize, out: *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let xprv = raw_try!("xprv", unsafe { read_xprv(xprv_ptr) });
+        let scheme = raw_try!("scheme", read_derivation_scheme(scheme));
+        let path = raw_try!("indices", unsafe { read_data_u32(indices_ptr, indices_sz) });
+        let child = derive_private_path(&xprv, scheme, &path);
+        raw_try!("out", unsafe { write_xprv(&child, out) });
+        raw::SUCCESS
+    })
+}
+
+/// derive the public key at the given textual derivation path (which
+/// can't contain hardened indices)
+#[no_mangle]
+pub extern "C" fn wallet_derive_public_path(xpub_ptr: *const c_uchar, scheme: u32, path_ptr: *const c_uchar, path_sz: usize, out: *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let xpub = raw_try!("xpub", unsafe { read_xpub(xpub_ptr) });
+        let scheme = raw_try!("scheme", read_derivation_scheme(scheme));
+        let path = raw_try!("path", unsafe { read_derivation_path(path_ptr, path_sz) });
+        let child = raw_try!("path", derive_public_path(&xpub, scheme, &path));
+        raw_try!("out", unsafe { write_xpub(&child, out) });
+        raw::SUCCESS
+    })
+}
+
+/// derive the public key at the given derivation path (array of indices)
+#[no_mangle]
+pub extern "C" fn wallet_derive_public_indices(xpub_ptr: *const c_uchar, scheme: u32, indices_ptr: *const c_uint, indices_sz: usize, out: *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let xpub = raw_try!("xpub", unsafe { read_xpub(xpub_ptr) });
+        let scheme = raw_try!("scheme", read_derivation_scheme(scheme));
+        let path = raw_try!("indices", unsafe { read_data_u32(indices_ptr, indices_sz) });
+        let child = raw_try!("indices", derive_public_path(&xpub, scheme, &path));
+        raw_try!("out", unsafe { write_xpub(&child, out) });
+        raw::SUCCESS
+    })
 }
 
+/// maximum number of keys derived by `wallet_derive_public_range`
+const MAX_DERIVE_RANGE : u32 = 10_000;
+
+/// derive the `count` child public keys of the given key, starting at the
+/// index `start`. The keys are returned one after the other (`count`
+/// times `XPUB_SIZE` bytes, `count` being at most `MAX_DERIVE_RANGE`).
+#[no_mangle]
+pub extern "C" fn wallet_derive_public_range(xpub_ptr: *const c_uchar, scheme: u32, start: u32, count: u32, out: *mut *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let xpub = raw_try!("xpub", unsafe { read_xpub(xpub_ptr) });
+        let scheme = raw_try!("scheme", read_derivation_scheme(scheme));
+        if count > MAX_DERIVE_RANGE {
+            return raw_fail!(raw::INVALID_ARGUMENT, Some("count"), "at most {} keys can be derived at once", MAX_DERIVE_RANGE);
+        }
+        if start.checked_add(count).map(|end| end > HARDENED).unwrap_or(true) {
+            return raw_fail!(raw::INVALID_ARGUMENT, Some("count"), "the range must not contain hardened indices");
+        }
+        let mut keys = Vec::with_capacity(count as usize * hdwallet::XPUB_SIZE);
+        for index in start..(start + count) {
+            let child = raw_try!("start", xpub.derive(scheme, index));
+            keys.extend_from_slice(child.as_ref());
+        }
+        raw_try!("out", unsafe { return_data(keys, out) })
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn wallet_sign(xprv_ptr: *const c_uchar, msg_ptr: *const c_uchar, msg_sz: usize, out: *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let xprv = raw_try!("xprv", unsafe { read_xprv(xprv_ptr) });
+        let msg = raw_try!("msg", unsafe { read_data(msg_ptr, msg_sz) });
+        let signature : hdwallet::Signature<Vec<u8>> = xprv.sign(&msg[..]);
+        raw_try!("out", unsafe { write_signature(&signature, out) });
+        raw::SUCCESS
+    })
+}
+
+/// returns `SUCCESS` if the signature is valid, `FAILED` otherwise
 #[no_mangle]
-pub extern "C" fn wallet_sign(xprv_ptr: *const c_uchar, msg_ptr: *const c_uchar, msg_sz: usize, out: *mut c_uchar) {
-    let xprv = unsafe { read_xprv(xprv_ptr) };
-    let msg = unsafe { read_data(msg_ptr, msg_sz) };
-    let signature : hdwallet::Signature<Vec<u8>> = xprv.sign(&msg[..]);
-    unsafe { write_signature(&signature, out) }
+pub extern "C" fn wallet_verify(xpub_ptr: *const c_uchar, msg_ptr: *const c_uchar, msg_sz: usize, sig_ptr: *const c_uchar) -> i32 {
+    raw::call(|| {
+        let xpub = raw_try!("xpub", unsafe { read_xpub(xpub_ptr) });
+        let msg = raw_try!("msg", unsafe { read_data(msg_ptr, msg_sz) });
+        let signature = raw_try!("signature", unsafe { read_signature::<Vec<u8>>(sig_ptr) });
+        if xpub.verify(&msg, &signature) { raw::SUCCESS } else { raw::FAILED }
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn wallet_verify(xpub_ptr: *const c_uchar, msg_ptr: *const c_uchar, msg_sz: usize, sig_ptr: *const c_uchar) -> bool {
-    let xpub = unsafe { read_xpub(xpub_ptr) };
-    let msg = unsafe { read_data(msg_ptr, msg_sz) };
-    let signature = unsafe { read_signature::<Vec<u8>>(sig_ptr) };
-    xpub.verify(&msg, &signature)
+pub extern "C" fn paper_scramble(iv_ptr: *const c_uchar, pass_ptr: *const c_uchar, pass_sz: usize, input_ptr: *const c_uchar, input_sz: usize, out: *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let iv = raw_try!("iv", unsafe { read_data(iv_ptr, paperwallet::IV_SIZE) });
+        let pass = raw_try!("pass", unsafe { read_secret(pass_ptr, pass_sz) });
+        let input = raw_try!("input", unsafe { read_secret(input_ptr, input_sz) });
+        let output = paperwallet::scramble(&iv[..], &pass[..], &input[..]);
+        raw_try!("out", unsafe { write_data(&output[..], out) });
+        raw::SUCCESS
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn paper_scramble(iv_ptr: *const c_uchar, pass_ptr: *const c_uchar, pass_sz: usize, input_ptr: *const c_uchar, input_sz: usize, out: *mut c_uchar) {
-    let iv = unsafe { read_data(iv_ptr, paperwallet::IV_SIZE) };
-    let pass = unsafe { read_data(pass_ptr, pass_sz) };
-    let input = unsafe { read_data(input_ptr, input_sz) };
-    let output = paperwallet::scramble(&iv[..], &pass[..], &input[..]);
-    unsafe { write_data(&output[..], out) }
+pub extern "C" fn paper_unscramble(pass_ptr: *const c_uchar, pass_sz: usize, input_ptr: *const c_uchar, input_sz: usize, out: *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let pass = raw_try!("pass", unsafe { read_secret(pass_ptr, pass_sz) });
+        let input = raw_try!("input", unsafe { read_data(input_ptr, input_sz) });
+        if input.len() < paperwallet::IV_SIZE {
+            return raw_fail!(raw::INVALID_ARGUMENT, Some("input"), "input must be at least {} bytes", paperwallet::IV_SIZE);
+        }
+        let output = secret::SecretBytes::new(paperwallet::unscramble(&pass[..], &input[..]));
+        raw_try!("out", unsafe { write_data(&output[..], out) });
+        raw::SUCCESS
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn paper_unscramble(pass_ptr: *const c_uchar, pass_sz: usize, input_ptr: *const c_uchar, input_sz: usize, out: *mut c_uchar) {
-    let pass = unsafe { read_data(pass_ptr, pass_sz) };
-    let input = unsafe { read_data(input_ptr, input_sz) };
-    let output = paperwallet::unscramble(&pass[..], &input[..]);
-    unsafe { write_data(&output[..], out) }
+pub extern "C" fn blake2b_256(msg_ptr: *const c_uchar, msg_sz: usize, out: *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let mut b2b = Blake2b::new(32);
+        let mut outv = [0;32];
+        let msg = raw_try!("msg", unsafe { read_data(msg_ptr, msg_sz) });
+        b2b.input(&msg);
+        b2b.result(&mut outv);
+        raw_try!("out", unsafe { write_data(&outv, out) });
+        raw::SUCCESS
+    })
+}
+
+/// check the address is the address of the given public key: the root of
+/// the address is recomputed from the key and the address attributes
+fn is_address_of(xpub: &hdwallet::XPub, addr: &address::ExtendedAddr) -> bool {
+    let expected = address::ExtendedAddr::new(
+        address::AddrType::ATPubKey,
+        address::SpendingData::PubKeyASD(xpub.clone()),
+        addr.attributes.clone()
+    );
+    &expected == addr
 }
 
+/// returns `SUCCESS` if the address (CBOR) belongs to the public key,
+/// `FAILED` otherwise. The payload of the address does not need to be
+/// decrypted: the root is recomputed with the attributes of the address.
 #[no_mangle]
-pub extern "C" fn blake2b_256(msg_ptr: *const c_uchar, msg_sz: usize, out: *mut c_uchar) {
-    let mut b2b = Blake2b::new(32);
-    let mut outv = [0;32];
-    let msg = unsafe { read_data(msg_ptr, msg_sz) };
-    b2b.input(&msg);
-    b2b.result(&mut outv);
-    unsafe { write_data(&outv, out) }
+pub extern "C" fn wallet_address_is_of(xpub_ptr: *const c_uchar, addr_ptr: *const c_uchar, addr_sz: usize) -> i32 {
+    raw::call(|| {
+        let xpub = raw_try!("xpub", unsafe { read_xpub(xpub_ptr) });
+        let addr_bytes = raw_try!("addr", unsafe { read_data(addr_ptr, addr_sz) });
+        let addr = raw_try!("addr", address::ExtendedAddr::from_bytes(&addr_bytes));
+        if is_address_of(&xpub, &addr) { raw::SUCCESS } else { raw::FAILED }
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn wallet_public_to_address(xpub_ptr: *const c_uchar, payload_ptr: *const c_uchar, payload_sz: usize, out: *mut c_uchar) -> u32 {
-    let xpub = unsafe { read_xpub(xpub_ptr) };
-    let payload = unsafe { read_data(payload_ptr, payload_sz) };
+pub extern "C" fn wallet_public_to_address(xpub_ptr: *const c_uchar, payload_ptr: *const c_uchar, payload_sz: usize, out: *mut *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let xpub = raw_try!("xpub", unsafe { read_xpub(xpub_ptr) });
+        let payload = raw_try!("payload", unsafe { read_data(payload_ptr, payload_sz) });
 
-    let hdap = hdpayload::HDAddressPayload::from_vec(payload);
+        let hdap = hdpayload::HDAddressPayload::from_vec(payload);
 
-    let addr_type = address::AddrType::ATPubKey;
-    let sd = address::SpendingData::PubKeyASD(xpub.clone());
-    let attrs = address::Attributes::new_bootstrap_era(Some(hdap));
-    let ea = address::ExtendedAddr::new(addr_type, sd, attrs);
+        let addr_type = address::AddrType::ATPubKey;
+        let sd = address::SpendingData::PubKeyASD(xpub.clone());
+        let attrs = address::Attributes::new_bootstrap_era(Some(hdap));
+        let ea = address::ExtendedAddr::new(addr_type, sd, attrs);
 
-    let ea_bytes = ea.to_bytes();
+        let ea_bytes = ea.to_bytes();
 
-    unsafe { write_data(&ea_bytes, out) }
+        raw_try!("out", unsafe { return_data(ea_bytes, out) })
+    })
+}
 
-    return ea_bytes.len() as u32;
+/// decode the address from its base58 form. The CRC32 of the address is
+/// checked when decoding the CBOR of the address.
+fn address_from_base58(address: &str) -> Result<address::ExtendedAddr> {
+    let bytes = base58::decode(address).map_err(|e| Error::ErrorBase58(format!("{:?}", e)))?;
+    Ok(address::ExtendedAddr::from_bytes(&bytes)?)
 }
 
+/// returns the CBOR of the address given in base58 (e.g. `DdzFF...`)
 #[no_mangle]
-pub extern "C" fn wallet_address_get_payload(addr_ptr: *const c_uchar, addr_sz: usize, out: *mut c_uchar) -> u32 {
-    let addr_bytes = unsafe { read_data(addr_ptr, addr_sz) };
-    match address::ExtendedAddr::from_bytes(&addr_bytes).ok() {
-        None => (-1i32) as u32,
-        Some(r)  => {
-            match r.attributes.derivation_path {
-                None        => 0,
-                Some(dpath) => {
-                    unsafe { write_data(dpath.as_ref(), out) };
-                    dpath.as_ref().len() as u32
-                }
+pub extern "C" fn wallet_address_from_base58(str_ptr: *const c_uchar, str_sz: usize, out: *mut *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let str_bytes = raw_try!("address", unsafe { read_data(str_ptr, str_sz) });
+        let address_str = raw_try!("address", String::from_utf8(str_bytes));
+        let ea = raw_try!("address", address_from_base58(&address_str));
+
+        raw_try!("out", unsafe { return_data(ea.to_bytes(), out) })
+    })
+}
+
+/// returns the base58 form of the address given in CBOR
+#[no_mangle]
+pub extern "C" fn wallet_address_to_base58(addr_ptr: *const c_uchar, addr_sz: usize, out: *mut *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let addr_bytes = raw_try!("addr", unsafe { read_data(addr_ptr, addr_sz) });
+        let addr = raw_try!("addr", address::ExtendedAddr::from_bytes(&addr_bytes));
+
+        raw_try!("out", unsafe { return_data(format!("{}", addr).into_bytes(), out) })
+    })
+}
+
+/// the script address of the validator script with the given attributes
+fn script_address(validator: &address::Script, attributes: address::Attributes) -> address::ExtendedAddr {
+    address::ExtendedAddr::new(
+        address::AddrType::ATScript,
+        address::SpendingData::ScriptASD(validator.clone()),
+        attributes
+    )
+}
+
+/// the version of the multisig validator scripts (see `Multisig`), the
+/// version 0 being the one of the Plutus core scripts
+const MULTISIG_SCRIPT_VERSION : u16 = 0x4d53;
+
+/// an m-of-n multisig validator: the redeemer unlocking it holds the
+/// signatures of the transaction by at least `required` distinct keys.
+///
+/// The validator script is `required` (1 byte), the number of keys (1
+/// byte) then the keys (`XPUB_SIZE` bytes each). The redeemer script is a
+/// list of signatures, each one being the index of the key (1 byte) then
+/// the signature (`SIGNATURE_SIZE` bytes). These scripts are not Plutus
+/// core programs: they are evaluated by `xtx_verify` only.
+struct Multisig {
+    required: usize,
+    keys: Vec<hdwallet::XPub>
+}
+impl Multisig {
+    fn new(required: usize, keys: Vec<hdwallet::XPub>) -> Result<Self> {
+        if keys.len() > u8::max_value() as usize {
+            return Err(Error::ErrorMultisig(format!("at most {} keys, not {}", u8::max_value(), keys.len())));
+        }
+        if required == 0 || required > keys.len() {
+            return Err(Error::ErrorMultisig(format!("between 1 and {} signatures can be required, not {}", keys.len(), required)));
+        }
+        Ok(Multisig { required: required, keys: keys })
+    }
+
+    fn to_script(&self) -> address::Script {
+        let mut script = vec![self.required as u8, self.keys.len() as u8];
+        for key in self.keys.iter() { script.extend_from_slice(key.as_ref()); }
+        address::Script::new(MULTISIG_SCRIPT_VERSION, script)
+    }
+
+    /// the multisig validator of the script, `None` if it is not one
+    fn from_script(validator: &address::Script) -> Option<Self> {
+        if validator.version != MULTISIG_SCRIPT_VERSION || validator.script.len() < 2 { return None; }
+        let (required, count) = (validator.script[0] as usize, validator.script[1] as usize);
+        let keys = &validator.script[2..];
+        if keys.len() != count * hdwallet::XPUB_SIZE { return None; }
+        let keys = keys.chunks(hdwallet::XPUB_SIZE).map(hdwallet::XPub::from_slice).collect::<result::Result<Vec<_>, _>>().ok()?;
+        Multisig::new(required, keys).ok()
+    }
+
+    /// the redeemer script of the given signatures (index of the key and
+    /// signature of the transaction)
+    fn redeemer(&self, signatures: &[MultisigSignature]) -> Result<address::Script> {
+        let mut script = Vec::with_capacity(signatures.len() * (1 + hdwallet::SIGNATURE_SIZE));
+        for signature in signatures {
+            if signature.index as usize >= self.keys.len() {
+                return Err(Error::ErrorMultisig(format!("no key at index {}", signature.index)));
             }
+            if signature.signature.len() != hdwallet::SIGNATURE_SIZE {
+                return Err(Error::ErrorMultisig(format!("a signature has {} bytes, not {}", hdwallet::SIGNATURE_SIZE, signature.signature.len())));
+            }
+            script.push(signature.index);
+            script.extend_from_slice(&signature.signature);
+        }
+        Ok(address::Script::new(MULTISIG_SCRIPT_VERSION, script))
+    }
+
+    /// whether the redeemer holds enough valid signatures of the
+    /// transaction by distinct keys
+    fn verify(&self, cfg: &Config, tx: &tx::Tx, redeemer: &address::Script) -> bool {
+        if redeemer.version != MULTISIG_SCRIPT_VERSION || redeemer.script.len() % (1 + hdwallet::SIGNATURE_SIZE) != 0 {
+            return false;
         }
+        let mut signers = BTreeSet::new();
+        for chunk in redeemer.script.chunks(1 + hdwallet::SIGNATURE_SIZE) {
+            let index = chunk[0] as usize;
+            let key = match self.keys.get(index) { Some(key) => key, None => return false };
+            let signature = match hdwallet::Signature::from_slice(&chunk[1..]) { Ok(signature) => signature, Err(_) => return false };
+            if ! tx::TxInWitness::PkWitness(key.clone(), signature).verify_tx(cfg, tx) { return false; }
+            signers.insert(index);
+        }
+        signers.len() >= self.required
     }
 }
 
+/// a signature of the transaction by one of the keys of a multisig
+/// validator
+#[derive(Deserialize)]
+struct MultisigSignature {
+    /// the index of the key in the validator
+    index: u8,
+    signature: Vec<u8>
+}
+
+#[derive(Deserialize)]
+struct MultisigAddressInput {
+    /// the number of signatures required to spend
+    required: usize,
+    /// the public keys (64 bytes each) allowed to sign
+    public_keys: Vec<Vec<u8>>
+}
+
+#[derive(Serialize)]
+struct MultisigAddressOutput {
+    /// the script address (CBOR)
+    address: Vec<u8>,
+    /// the validator script, to give back to `xtx_assemble`
+    validator: ScriptInput
+}
+
+/// the script address of an m-of-n multisig validator (see `Multisig`)
+/// and its validator script
 #[no_mangle]
-pub extern "C" fn wallet_payload_initiate(xpub_ptr: *const c_uchar, out: *mut c_uchar) {
-    let xpub = unsafe { read_xpub(xpub_ptr) };
-    let hdkey = hdpayload::HDKey::new(&xpub);
-    unsafe { write_data(hdkey.as_ref(), out); }
+pub extern "C" fn xaddress_multisig(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : MultisigAddressInput = input_json!(output_ptr, input_ptr, input_sz);
+    let keys = jrpc_try!(output_ptr, input.public_keys.iter().map(|key| hdwallet::XPub::from_slice(key)).collect::<result::Result<Vec<_>, _>>());
+    let multisig = jrpc_try!(output_ptr, Multisig::new(input.required, keys));
+    let validator = multisig.to_script();
+    let ea = script_address(&validator, address::Attributes::new_bootstrap_era(None));
+    jrpc_ok!(output_ptr, MultisigAddressOutput {
+        address: ea.to_bytes(),
+        validator: ScriptInput { version: validator.version, script: validator.script }
+    })
 }
 
+/// returns the script address (CBOR) of the given script: the root of the
+/// address is the hash of the script and its version
 #[no_mangle]
-pub extern "C" fn wallet_payload_encrypt(key_ptr: *const c_uchar, path_array: *const c_uint, path_sz: usize, out: *mut c_uchar) -> u32 {
-    let key_bytes = unsafe { read_data(key_ptr, hdpayload::HDKEY_SIZE) };
-    let path_vec = unsafe { read_data_u32(path_array, path_sz) };
-    let hdkey = hdpayload::HDKey::from_slice(&key_bytes).unwrap();
+pub extern "C" fn wallet_script_to_address(version: u32, script_ptr: *const c_uchar, script_sz: usize, out: *mut *mut c_uchar) -> i32 {
+    raw::call(|| {
+        if version > u16::max_value() as u32 {
+            return raw_fail!(raw::INVALID_ARGUMENT, Some("version"), "the script version must fit in 16 bits");
+        }
+        let script = raw_try!("script", unsafe { read_data(script_ptr, script_sz) });
 
-    let path = hdpayload::Path::new(path_vec);
+        let ea = script_address(&address::Script::new(version as u16, script), address::Attributes::new_bootstrap_era(None));
 
-    let payload = hdkey.encrypt_path(&path);
+        raw_try!("out", unsafe { return_data(ea.to_bytes(), out) })
+    })
+}
 
-    unsafe { write_data(payload.as_ref(), out) };
-    payload.len() as u32
+/// the redeem (AVVM) address of the redemption public key
+fn redeem_address(key: &redeem::PublicKey) -> address::ExtendedAddr {
+    address::ExtendedAddr::new(
+        address::AddrType::ATRedeem,
+        address::SpendingData::RedeemASD(key.clone()),
+        address::Attributes::new_bootstrap_era(None)
+    )
 }
 
+/// returns the redeem address (CBOR) of the redemption private key (32
+/// bytes, as printed in base64 on the ADA vending certificates)
 #[no_mangle]
-pub extern "C" fn wallet_payload_decrypt(key_ptr: *const c_uchar, payload_ptr: *const c_uchar, payload_sz: usize, out: *mut c_uint) -> u32 {
-    let key_bytes = unsafe { read_data(key_ptr, hdpayload::HDKEY_SIZE) };
-    let payload_bytes = unsafe { read_data(payload_ptr, payload_sz) };
+pub extern "C" fn wallet_redeem_to_address(key_ptr: *const c_uchar, key_sz: usize, out: *mut *mut c_uchar) -> i32 {
+    raw::call(|| {
+        if key_sz != redeem::PRIVATEKEY_SIZE {
+            return raw_fail!(raw::INVALID_ARGUMENT, Some("key"), "a redemption key has {} bytes, not {}", redeem::PRIVATEKEY_SIZE, key_sz);
+        }
+        let key_bytes = raw_try!("key", unsafe { read_secret(key_ptr, key_sz) });
+        let key = secret::Secret::new(raw_try!("key", redeem::PrivateKey::from_slice(&key_bytes)));
+        let ea = redeem_address(&key.public());
 
-    let hdkey = hdpayload::HDKey::from_slice(&key_bytes).unwrap();
-    let payload = hdpayload::HDAddressPayload::from_bytes(&payload_bytes);
+        raw_try!("out", unsafe { return_data(ea.to_bytes(), out) })
+    })
+}
 
-    match hdkey.decrypt_path(&payload) {
-        None       => 0xffffffff,
-        Some(path) => {
-            let v = path.as_ref();
-            unsafe { write_data_u32(v, out) };
-            v.len() as u32
+#[no_mangle]
+pub extern "C" fn wallet_address_get_payload(addr_ptr: *const c_uchar, addr_sz: usize, out: *mut *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let addr_bytes = raw_try!("addr", unsafe { read_data(addr_ptr, addr_sz) });
+        let addr = raw_try!("addr", address::ExtendedAddr::from_bytes(&addr_bytes));
+        match addr.attributes.derivation_path {
+            None        => raw_try!("out", unsafe { return_data(Vec::new(), out) }),
+            Some(dpath) => raw_try!("out", unsafe { return_data(dpath.as_ref().to_vec(), out) })
         }
-    }
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn wallet_txin_create(txid_ptr: *const c_uchar, index: u32, out: *mut c_uchar) -> u32 {
-    let txid_bytes = unsafe { read_data(txid_ptr, HASH_SIZE) };
+pub extern "C" fn wallet_payload_initiate(xpub_ptr: *const c_uchar, out: *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let xpub = raw_try!("xpub", unsafe { read_xpub(xpub_ptr) });
+        let hdkey = hdpayload::HDKey::new(&xpub);
+        raw_try!("out", unsafe { write_data(hdkey.as_ref(), out) });
+        raw::SUCCESS
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn wallet_payload_encrypt(key_ptr: *const c_uchar, path_array: *const c_uint, path_sz: usize, out: *mut *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let hdkey = raw_try!("key", unsafe { read_hdkey(key_ptr) });
+        let path_vec = raw_try!("path", unsafe { read_data_u32(path_array, path_sz) });
 
-    let txid = tx::TxId::from_slice(&txid_bytes).unwrap();
+        let path = hdpayload::Path::new(path_vec);
 
-    let txin = tx::TxIn::new(txid, index);
-    let out_buf = encode_to_cbor(&txin).unwrap();
+        let payload = hdkey.encrypt_path(&path);
 
-    unsafe { write_data(&out_buf, out) }
-    out_buf.len() as u32
+        raw_try!("out", unsafe { return_data(payload.as_ref().to_vec(), out) })
+    })
 }
 
+/// returns the length (in bytes) of the decrypted path or `FAILED` if the
+/// payload could not be decrypted with the given key
 #[no_mangle]
-pub extern "C" fn wallet_txout_create(ea_ptr: *const c_uchar, ea_sz: usize, amount: u32, out: *mut c_uchar) -> u32 {
-    let ea_bytes = unsafe { read_data(ea_ptr, ea_sz) };
-
-    let ea = address::ExtendedAddr::from_bytes(&ea_bytes).unwrap();
-    let coin = coin::Coin::new(amount as u64).unwrap();
+pub extern "C" fn wallet_payload_decrypt(key_ptr: *const c_uchar, payload_ptr: *const c_uchar, payload_sz: usize, out: *mut *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let hdkey = raw_try!("key", unsafe { read_hdkey(key_ptr) });
+        let payload_bytes = raw_try!("payload", unsafe { read_data(payload_ptr, payload_sz) });
 
-    let txout = tx::TxOut::new(ea, coin);
-    let out_buf = encode_to_cbor(&txout).unwrap();
+        let payload = hdpayload::HDAddressPayload::from_bytes(&payload_bytes);
 
-    unsafe { write_data(&out_buf, out) }
-    out_buf.len() as u32
+        match hdkey.decrypt_path(&payload) {
+            None       => raw::FAILED,
+            Some(path) => raw_try!("out", unsafe { return_data_u32(path.as_ref(), out) })
+        }
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn wallet_tx_new(out: *mut c_uchar) -> u32 {
-    let tx = tx::Tx::new();
-    let out_buf = encode_to_cbor(&tx).unwrap();
-    unsafe { write_data(&out_buf, out) }
-    out_buf.len() as u32
+pub extern "C" fn wallet_txin_create(txid_ptr: *const c_uchar, index: u32, out: *mut *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let txid_bytes = raw_try!("txid", unsafe { read_data(txid_ptr, HASH_SIZE) });
+
+        let txid = raw_try!("txid", tx::TxId::from_slice(&txid_bytes));
+
+        let txin = tx::TxIn::new(txid, index);
+        let out_buf = raw_try!("txin", encode_to_cbor(&txin));
+
+        raw_try!("out", unsafe { return_data(out_buf, out) })
+    })
+}
+
+/// the amount (in lovelace) given to the raw functions as its low and high
+/// 32 bits, checking it is a valid coin value
+fn read_amount(amount_lo: u32, amount_hi: u32) -> result::Result<coin::Coin, coin::Error> {
+    coin::Coin::new(((amount_hi as u64) << 32) | (amount_lo as u64))
+}
+
+/// the amount (in lovelace) given to the jrpc functions as a string of
+/// decimal digits, checking it is a valid coin value
+fn parse_amount(amount: &str) -> Result<coin::Coin> {
+    let value = amount.parse::<u64>().map_err(|_| Error::ErrorInvalidAmount(amount.to_string()))?;
+    Ok(coin::Coin::new(value)?)
 }
 
 #[no_mangle]
-pub extern "C" fn wallet_tx_add_txin(tx_ptr: *const c_uchar, tx_sz: usize, txin_ptr: *const c_uchar, txin_sz: usize, out: *mut c_uchar) -> u32 {
-    let tx_bytes = unsafe { read_data(tx_ptr, tx_sz) };
-    let txin_bytes = unsafe { read_data(txin_ptr, txin_sz) };
+pub extern "C" fn wallet_txout_create(ea_ptr: *const c_uchar, ea_sz: usize, amount_lo: u32, amount_hi: u32, out: *mut *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let ea_bytes = raw_try!("address", unsafe { read_data(ea_ptr, ea_sz) });
 
-    let mut tx : tx::Tx = decode_from_cbor(&tx_bytes).unwrap();
-    let txin = decode_from_cbor(&txin_bytes).unwrap();
+        let ea = raw_try!("address", address::ExtendedAddr::from_bytes(&ea_bytes));
+        let coin = raw_try!("amount", read_amount(amount_lo, amount_hi));
 
-    tx.add_input(txin);
+        let txout = tx::TxOut::new(ea, coin);
+        let out_buf = raw_try!("txout", encode_to_cbor(&txout));
 
-    let out_buf = encode_to_cbor(&tx).unwrap();
-    unsafe { write_data(&out_buf, out) }
-    out_buf.len() as u32
+        raw_try!("out", unsafe { return_data(out_buf, out) })
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn wallet_tx_add_txout(tx_ptr: *const c_uchar, tx_sz: usize, txout_ptr: *const c_uchar, txout_sz: usize, out: *mut c_uchar) -> u32 {
-    let tx_bytes = unsafe { read_data(tx_ptr, tx_sz) };
-    let txout_bytes = unsafe { read_data(txout_ptr, txout_sz) };
+pub extern "C" fn wallet_tx_new(out: *mut *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let tx = tx::Tx::new();
+        let out_buf = raw_try!("tx", encode_to_cbor(&tx));
+        raw_try!("out", unsafe { return_data(out_buf, out) })
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn wallet_tx_add_txin(tx_ptr: *const c_uchar, tx_sz: usize, txin_ptr: *const c_uchar, txin_sz: usize, out: *mut *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let tx_bytes = raw_try!("tx", unsafe { read_data(tx_ptr, tx_sz) });
+        let txin_bytes = raw_try!("txin", unsafe { read_data(txin_ptr, txin_sz) });
 
-    let mut tx : tx::Tx = decode_from_cbor(&tx_bytes).unwrap();
-    let txout = decode_from_cbor(&txout_bytes).unwrap();
+        let mut tx : tx::Tx = raw_try!("tx", decode_from_cbor(&tx_bytes));
+        let txin = raw_try!("txin", decode_from_cbor(&txin_bytes));
 
-    tx.add_output(txout);
+        tx.add_input(txin);
 
-    let out_buf = encode_to_cbor(&tx).unwrap();
-    unsafe { write_data(&out_buf, out) }
-    out_buf.len() as u32
+        let out_buf = raw_try!("tx", encode_to_cbor(&tx));
+        raw_try!("out", unsafe { return_data(out_buf, out) })
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn wallet_tx_sign(cfg_ptr: *const c_uchar, cfg_size: usize, xprv_ptr: *const c_uchar, tx_ptr: *const c_uchar, tx_sz: usize, out: *mut c_uchar) {
-    let cfg_bytes : Vec<u8> = unsafe { read_data(cfg_ptr, cfg_size) };
-    let cfg_str = String::from_utf8(cfg_bytes).unwrap();
-    let cfg : Config = serde_json::from_str(cfg_str.as_str()).unwrap();
-    let xprv = unsafe { read_xprv(xprv_ptr) };
-    let tx_bytes = unsafe { read_data(tx_ptr, tx_sz) };
+pub extern "C" fn wallet_tx_add_txout(tx_ptr: *const c_uchar, tx_sz: usize, txout_ptr: *const c_uchar, txout_sz: usize, out: *mut *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let tx_bytes = raw_try!("tx", unsafe { read_data(tx_ptr, tx_sz) });
+        let txout_bytes = raw_try!("txout", unsafe { read_data(txout_ptr, txout_sz) });
 
-    let tx = decode_from_cbor(&tx_bytes).unwrap();
+        let mut tx : tx::Tx = raw_try!("tx", decode_from_cbor(&tx_bytes));
+        let txout = raw_try!("txout", decode_from_cbor(&txout_bytes));
 
-    let txinwitness = tx::TxInWitness::new(&cfg, &xprv, &tx);
+        tx.add_output(txout);
 
-    let signature = match txinwitness {
-        tx::TxInWitness::PkWitness(_, sig) => sig,
-        _ => unimplemented!() // this should never happen as we are signing for the tx anyway
-    };
-    unsafe { write_signature(&signature, out) }
+        let out_buf = raw_try!("tx", encode_to_cbor(&tx));
+        raw_try!("out", unsafe { return_data(out_buf, out) })
+    })
 }
 
+#[no_mangle]
+pub extern "C" fn wallet_tx_sign(cfg_ptr: *const c_uchar, cfg_size: usize, xprv_ptr: *const c_uchar, tx_ptr: *const c_uchar, tx_sz: usize, out: *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let cfg = raw_try!("cfg", unsafe { read_config(cfg_ptr, cfg_size) });
+        let xprv = raw_try!("xprv", unsafe { read_xprv(xprv_ptr) });
+        let tx_bytes = raw_try!("tx", unsafe { read_data(tx_ptr, tx_sz) });
+
+        let tx = raw_try!("tx", decode_from_cbor(&tx_bytes));
+
+        let txinwitness = tx::TxInWitness::new(&cfg, &xprv, &tx);
+
+        let signature = match txinwitness {
+            tx::TxInWitness::PkWitness(_, sig) => sig,
+            // this should never happen as we are signing for the tx anyway
+            _ => return raw_fail!(raw::FAILED, None, "unexpected witness type")
+        };
+        raw_try!("out", unsafe { write_signature(&signature, out) });
+        raw::SUCCESS
+    })
+}
+
+/// returns `SUCCESS` if the signature is valid, `FAILED` otherwise
 #[no_mangle]
 pub extern "C" fn wallet_tx_verify(cfg_ptr: *const c_uchar, cfg_size: usize, xpub_ptr: *const c_uchar, tx_ptr: *const c_uchar, tx_sz: usize, sig_ptr: *const c_uchar) -> i32 {
-    let cfg_bytes : Vec<u8> = unsafe { read_data(cfg_ptr, cfg_size) };
-    let cfg_str = String::from_utf8(cfg_bytes).unwrap();
-    let cfg : Config = serde_json::from_str(cfg_str.as_str()).unwrap();
-    let xpub = unsafe { read_xpub(xpub_ptr) };
-    let signature = unsafe { read_signature(sig_ptr) };
+    raw::call(|| {
+        let cfg = raw_try!("cfg", unsafe { read_config(cfg_ptr, cfg_size) });
+        let xpub = raw_try!("xpub", unsafe { read_xpub(xpub_ptr) });
+        let signature = raw_try!("signature", unsafe { read_signature(sig_ptr) });
 
-    let tx_bytes = unsafe { read_data(tx_ptr, tx_sz) };
-    let tx = decode_from_cbor(&tx_bytes).unwrap();
+        let tx_bytes = raw_try!("tx", unsafe { read_data(tx_ptr, tx_sz) });
+        let tx = raw_try!("tx", decode_from_cbor(&tx_bytes));
 
-    let txinwitness = tx::TxInWitness::PkWitness(xpub, signature);
+        let txinwitness = tx::TxInWitness::PkWitness(xpub, signature);
 
-    if txinwitness.verify_tx(&cfg, &tx) { 0 } else { -1 }
+        if txinwitness.verify_tx(&cfg, &tx) { raw::SUCCESS } else { raw::FAILED }
+    })
 }
 
 mod jrpc {
     use serde::{Serialize};
     use serde_json;
+    use super::secret;
     use std::os::raw::{c_uchar};
 
     #[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
-    struct Error {
+    pub struct Error {
         failed: bool,
         loc: String,
-        msg: String
+        msg: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub argument: Option<String>
     }
     impl Error {
-        fn new(loc: String, msg: String) -> Self { Error { failed: true, loc: loc, msg: msg } }
+        pub fn new(loc: String, msg: String) -> Self { Error { failed: true, loc: loc, msg: msg, argument: None } }
     }
 
-    pub fn fail(output_ptr: *mut c_uchar, file: &str, line: u32, msg: String) -> i32 {
-        let error = Error::new(format!("{} {}", file, line), msg);
+    fn output(output_ptr: *mut *mut c_uchar, output: String) -> i32 {
+        match unsafe { super::return_data(output.into_bytes(), output_ptr) } {
+            Ok(sz) => sz,
+            Err(err) => super::raw::fail(super::raw::INVALID_ARGUMENT, file!(), line!(), None, format!("{:?}", err))
+        }
+    }
 
-        let output = serde_json::to_string(&error).unwrap();
-        let output_bytes = output.into_bytes();
+    pub fn fail(output_ptr: *mut *mut c_uchar, file: &str, line: u32, msg: String) -> i32 {
+        let error = Error::new(format!("{} {}", file, line), msg);
 
-        unsafe { super::write_data(&output_bytes, output_ptr) };
-        output_bytes.len() as i32
+        let output = match serde_json::to_string(&error) {
+            Ok(output) => output,
+            Err(_) => r#"{"failed":true,"loc":"","msg":"the error could not be serialised"}"#.to_string()
+        };
+        self::output(output_ptr, output)
     }
 
     #[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
@@ -414,16 +1191,34 @@ mod jrpc {
         fn new(result: T) -> Self { Success { failed: false, result: result } }
     }
 
-    pub fn ok<T>(output_ptr: *mut c_uchar, result: T) -> i32
+    /// same as `ok` for the results holding private keys, seeds, entropy
+    /// or mnemonic phrases, the output is released with
+    /// `dealloc_output_secret`
+    pub fn ok_secret<T>(output_ptr: *mut *mut c_uchar, result: T) -> i32
         where T: Serialize
     {
         let succ = Success::new(result);
 
-        let output = serde_json::to_string(&succ).unwrap();
-        let output_bytes = output.into_bytes();
+        let output = match secret::to_json(&succ) {
+            Ok(output) => output,
+            Err(err) => return fail(output_ptr, file!(), line!(), format!("{:?}", err))
+        };
+        match unsafe { super::return_secret(output, output_ptr) } {
+            Ok(sz) => sz,
+            Err(err) => super::raw::fail(super::raw::INVALID_ARGUMENT, file!(), line!(), None, format!("{:?}", err))
+        }
+    }
+
+    pub fn ok<T>(output_ptr: *mut *mut c_uchar, result: T) -> i32
+        where T: Serialize
+    {
+        let succ = Success::new(result);
 
-        unsafe { super::write_data(&output_bytes, output_ptr) };
-        output_bytes.len() as i32
+        let output = match serde_json::to_string(&succ) {
+            Ok(output) => output,
+            Err(err) => return fail(output_ptr, file!(), line!(), format!("{:?}", err))
+        };
+        self::output(output_ptr, output)
     }
 }
 
@@ -455,6 +1250,16 @@ macro_rules! jrpc_ok {
     });
 }
 
+/// same as `jrpc_ok!` for the results holding secrets (see `jrpc::ok_secret`)
+macro_rules! jrpc_ok_secret {
+    ($output_ptr:ident, $result:expr) => ({
+        jrpc::ok_secret($output_ptr, $result)
+    });
+    ($output_ptr:ident, $result:expr,) => ({
+        jrpc_ok_secret!($output_ptr, $result)
+    });
+}
+
 macro_rules! jrpc_try {
     ($output_ptr:ident, $expr:expr) => (match $expr {
         Ok(val) => val,
@@ -470,6 +1275,26 @@ enum Error {
     ErrorCBOR(cbor::Error),
     ErrorFEE(tx::fee::Error),
     ErrorWallet(wallet::Error),
+    ErrorHDWallet(hdwallet::Error),
+    ErrorBip39(bip39::Error),
+    ErrorInvalidHDKey,
+    ErrorInvalidHandle(i32),
+    ErrorDerivationPath(String),
+    ErrorCoinSelection(String),
+    ErrorBalanceBelowFee(u64, u64),
+    ErrorWitnessCount(usize, usize),
+    ErrorInvalidEnvelope(String),
+    ErrorCoin(coin::Error),
+    ErrorDerivationScheme(u32),
+    ErrorDerivationSchemeMismatch(hdwallet::DerivationScheme, hdwallet::DerivationScheme),
+    ErrorBase58(String),
+    ErrorRedeem(redeem::Error),
+    ErrorOutputTooLarge(usize),
+    ErrorInvalidAmount(String),
+    ErrorInvalidCbor(String),
+    ErrorMultisig(String),
+    ErrorDaedalusMnemonic(String),
+    ErrorNullPointer,
 }
 impl convert::From<string::FromUtf8Error> for Error {
     fn from(j: string::FromUtf8Error) -> Self { Error::ErrorUtf8(j) }
@@ -486,11 +1311,23 @@ impl convert::From<tx::fee::Error> for Error {
 impl convert::From<wallet::Error> for Error {
     fn from(j: wallet::Error) -> Self { Error::ErrorWallet(j) }
 }
+impl convert::From<redeem::Error> for Error {
+    fn from(j: redeem::Error) -> Self { Error::ErrorRedeem(j) }
+}
+impl convert::From<coin::Error> for Error {
+    fn from(j: coin::Error) -> Self { Error::ErrorCoin(j) }
+}
+impl convert::From<hdwallet::Error> for Error {
+    fn from(j: hdwallet::Error) -> Self { Error::ErrorHDWallet(j) }
+}
+impl convert::From<bip39::Error> for Error {
+    fn from(j: bip39::Error) -> Self { Error::ErrorBip39(j) }
+}
 
 type Result<T> = result::Result<T, Error>;
 
 fn input_string_(input_ptr: *const c_uchar, input_sz: usize) -> Result<String> {
-    let input_bytes : Vec<u8> = unsafe { read_data(input_ptr, input_sz) };
+    let input_bytes : Vec<u8> = unsafe { read_data(input_ptr, input_sz)? };
     let input = String::from_utf8(input_bytes)?;
 
     Ok(input)
@@ -506,79 +1343,2114 @@ macro_rules! input_json {
     });
 }
 
+/// same as `input_json!` for the inputs holding private keys, seeds or
+/// mnemonic phrases: the copy of the input is wiped once parsed
+macro_rules! input_secret_json {
+    ($output_ptr:ident, $input_ptr:ident, $input_sz:ident) => ({
+        let input = jrpc_try!($output_ptr, unsafe { read_secret($input_ptr, $input_sz) });
+        jrpc_try!($output_ptr, serde_json::from_slice(&input))
+    });
+    ($output_ptr:ident, $input_ptr:ident, $input_sz:ident,) => ({
+        input_secret_json!($output_ptr, $input_ptr, $input_sz)
+    });
+}
+
+#[derive(Deserialize)]
+struct CreateWalletInput {
+    seed: hdwallet::Seed,
+    derivation_scheme: hdwallet::DerivationScheme
+}
+
+/// create the wallet of the given seed, the wallet records the derivation
+/// scheme so the accounts and the addresses are always derived with it
 #[no_mangle]
-pub extern "C" fn xwallet_create(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut c_uchar) -> i32 {
-    let seed = input_json!(output_ptr, input_ptr, input_sz);
-    jrpc_ok!(output_ptr, Wallet::new_from_seed(&seed))
+pub extern "C" fn xwallet_create(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : CreateWalletInput = input_secret_json!(output_ptr, input_ptr, input_sz);
+    let seed = secret::Secret::new(input.seed);
+    let wallet = secret::Secret::new(Wallet::new_from_seed(&seed, input.derivation_scheme));
+    jrpc_ok_secret!(output_ptr, &*wallet)
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[derive(Deserialize)]
 struct WalletSpendInput {
-    wallet: Wallet,
+    wallet: secret::Secret<Wallet>,
     inputs: tx::Inputs,
     outputs: tx::Outputs,
-    change_addr: address::ExtendedAddr
+    change_addr: address::ExtendedAddr,
+    /// the input selection, the default selection of the wallet if none
+    #[serde(default)]
+    selection_policy: Option<coin_selection::Policy>,
+    /// the linear fee coefficients (only used with a selection policy),
+    /// the ones of the network by default
+    #[serde(default)]
+    linear_fee: Option<LinearFeeCoefficients>
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug)]
 struct WalletSpendOutput {
     cbor_encoded_tx: Vec<u8>,
     tx: tx::TxAux,
-    fee: tx::fee::Fee
+    fee: tx::fee::Fee,
+    /// the inputs spent by the transaction
+    inputs: Vec<tx::Input>
 }
 
-#[no_mangle]
-pub extern "C" fn xwallet_spend(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut c_uchar) -> i32 {
-    let input : WalletSpendInput = input_json!(output_ptr, input_ptr, input_sz);
-    let txaux = jrpc_try!(output_ptr, input.wallet.new_transaction(&input.inputs, &input.outputs, &input.change_addr));
-    let cbor = jrpc_try!(output_ptr, cbor::encode_to_cbor(&txaux.0));
-    jrpc_ok!(
-        output_ptr,
-        WalletSpendOutput {
-            cbor_encoded_tx: cbor,
-            tx: txaux.0,
-            fee: txaux.1
-        }
-    )
-}
+/// Input selection policies of `xwallet_spend`.
+///
+/// A policy picks, among the available inputs, the ones to spend to pay
+/// the outputs and the fee. The fee depending on the size of the
+/// transaction, it is given as a function of the selected inputs and of
+/// the presence of a change output.
+mod coin_selection {
+    use wallet_crypto::tx;
+    use super::{Error, Result};
+
+    /// maximum number of selections tried by the branch and bound search
+    const MAX_EXACT_MATCH_TRIES : usize = 10_000;
+
+    #[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+    #[serde(rename_all = "snake_case")]
+    pub enum Policy {
+        /// spend the largest inputs first
+        LargestFirst,
+        /// spend the inputs in the given order (oldest first)
+        FirstInFirstOut,
+        /// the random-improve selection of cardano-sl, `seed` initialises
+        /// the random generator
+        RandomImprove { seed: u32 },
+        /// search the inputs paying exactly the outputs and the fee, not
+        /// to have a change output
+        ExactMatch,
+    }
 
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
-struct CreateWalletAccount {
-    wallet: Wallet,
-    account: u32
-}
+    /// the selected inputs (indices in the available inputs), the fee and
+    /// the change (0 if there is no change output)
+    pub struct Selection {
+        pub inputs: Vec<usize>,
+        pub fee: u64,
+        pub change: u64,
+    }
 
-#[no_mangle]
-pub extern "C" fn xwallet_account(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut c_uchar) -> i32 {
-    let input : CreateWalletAccount = input_json!(output_ptr, input_ptr, input_sz);
-    jrpc_ok!(
-        output_ptr,
-        input.wallet.account(input.account)
-    )
-}
+    pub type Fee<'a> = &'a Fn(&[&tx::Input], bool) -> Result<u64>;
 
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
-struct GenAddressesInput {
-    account: Account,
-    address_type: bip44::AddrType,
-    indices: Vec<u32>
-}
+    fn value(input: &tx::Input) -> u64 { u64::from(input.value.value) }
 
-#[no_mangle]
-pub extern "C" fn xwallet_addresses(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut c_uchar) -> i32 {
-    let input : GenAddressesInput = input_json!(output_ptr, input_ptr, input_sz);
-    let addresses : Vec<address::ExtendedAddr> =
-        jrpc_try!(output_ptr, input.account.gen_addresses(input.address_type, input.indices));
+    fn add(a: u64, b: u64) -> Result<u64> {
+        a.checked_add(b).ok_or(Error::ErrorCoinSelection("the sum of the values overflows".to_string()))
+    }
+
+    fn sum<I: Iterator<Item = u64>>(values: I) -> Result<u64> {
+        let mut total = 0;
+        for value in values { total = add(total, value)?; }
+        Ok(total)
+    }
+
+    fn total(available: &[tx::Input], selected: &[usize]) -> Result<u64> {
+        sum(selected.iter().map(|i| value(&available[*i])))
+    }
+
+    fn refs<'a>(available: &'a [tx::Input], selected: &[usize]) -> Vec<&'a tx::Input> {
+        selected.iter().map(|i| &available[*i]).collect()
+    }
+
+    /// the selection if the selected inputs pay the outputs and the fee:
+    /// with a change output if there is enough left for it, otherwise the
+    /// remaining is added to the fee
+    fn complete(available: &[tx::Input], selected: &[usize], outputs: u64, fee: Fee) -> Result<Option<Selection>> {
+        let total = total(available, selected)?;
+        let inputs = refs(available, selected);
+        let fee_change = fee(&inputs, true)?;
+        let with_change = add(outputs, fee_change)?;
+        if total > with_change {
+            return Ok(Some(Selection { inputs: selected.to_vec(), fee: fee_change, change: total - with_change }));
+        }
+        let fee_no_change = fee(&inputs, false)?;
+        if total >= add(outputs, fee_no_change)? {
+            return Ok(Some(Selection { inputs: selected.to_vec(), fee: total - outputs, change: 0 }));
+        }
+        Ok(None)
+    }
+
+    /// add the inputs in the given order until they pay the outputs and the fee
+    fn in_order(available: &[tx::Input], order: Vec<usize>, outputs: u64, fee: Fee) -> Result<Selection> {
+        let mut selected = Vec::new();
+        for i in order {
+            selected.push(i);
+            if let Some(selection) = complete(available, &selected, outputs, fee)? {
+                return Ok(selection);
+            }
+        }
+        Err(Error::ErrorCoinSelection("not enough inputs to pay the outputs and the fee".to_string()))
+    }
+
+    fn distance(a: u64, b: u64) -> u64 { if a > b { a - b } else { b - a } }
+
+    /// xorshift generator, the selection does not need more than that
+    struct Random(u32);
+    impl Random {
+        fn next(&mut self, bound: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 17;
+            self.0 ^= self.0 << 5;
+            self.0 as usize % bound
+        }
+    }
+
+    fn random_improve(available: &[tx::Input], targets: &[u64], seed: u32, fee: Fee) -> Result<Selection> {
+        let mut random = Random(if seed == 0 { 0x9e3779b9 } else { seed });
+        let mut remaining : Vec<usize> = (0..available.len()).collect();
+        let mut targets = targets.to_vec();
+        targets.sort_by(|a, b| b.cmp(a));
+
+        // random phase: cover every output with randomly picked inputs
+        let mut picked : Vec<Vec<usize>> = Vec::new();
+        for target in targets.iter() {
+            let mut inputs = Vec::new();
+            while total(available, &inputs)? < *target {
+                if remaining.is_empty() {
+                    return Err(Error::ErrorCoinSelection("not enough inputs to pay the outputs".to_string()));
+                }
+                let i = random.next(remaining.len());
+                inputs.push(remaining.swap_remove(i));
+            }
+            picked.push(inputs);
+        }
+
+        // improve phase: add random inputs while they bring the value of
+        // the inputs of an output closer to twice the output, without
+        // going over three times the output
+        for (target, inputs) in targets.iter().zip(picked.iter_mut()) {
+            while ! remaining.is_empty() {
+                let i = random.next(remaining.len());
+                let current = total(available, inputs)?;
+                let improved = add(current, value(&available[remaining[i]]))?;
+                let ideal = target.saturating_mul(2);
+                let closer = distance(improved, ideal) < distance(current, ideal);
+                if ! closer || improved > target.saturating_mul(3) { break; }
+                inputs.push(remaining.swap_remove(i));
+            }
+        }
+
+        // pay the fee with more random inputs if needed
+        let outputs = sum(targets.iter().cloned())?;
+        let mut selected : Vec<usize> = picked.into_iter().flat_map(|inputs| inputs).collect();
+        loop {
+            if let Some(selection) = complete(available, &selected, outputs, fee)? {
+                return Ok(selection);
+            }
+            if remaining.is_empty() {
+                return Err(Error::ErrorCoinSelection("not enough inputs to pay the fee".to_string()));
+            }
+            let i = random.next(remaining.len());
+            selected.push(remaining.swap_remove(i));
+        }
+    }
+
+    /// branch and bound search of the inputs paying the outputs and the fee
+    /// without change: the remaining value must be lower than the cost of
+    /// a change output
+    fn exact_match(available: &[tx::Input], outputs: u64, fee: Fee) -> Result<Selection> {
+        let mut order : Vec<usize> = (0..available.len()).collect();
+        order.sort_by(|a, b| value(&available[*b]).cmp(&value(&available[*a])));
+        // value of the inputs not considered yet, at each depth
+        let mut left = vec![0; order.len() + 1];
+        for depth in (0..order.len()).rev() {
+            left[depth] = add(left[depth + 1], value(&available[order[depth]]))?;
+        }
+
+        match search(available, &order, &left, outputs, fee)? {
+            Some(selection) => Ok(selection),
+            None => Err(Error::ErrorCoinSelection("no exact match for the outputs and the fee".to_string()))
+        }
+    }
+
+    /// depth first search of the tree where the input `order[depth]` is
+    /// first added to the selection then left out, without recursion: the
+    /// current branch is the list of the depths of the inputs added
+    fn search(available: &[tx::Input], order: &[usize], left: &[u64], outputs: u64, fee: Fee) -> Result<Option<Selection>> {
+        let mut added : Vec<usize> = Vec::new();
+        let mut depth = 0;
+        for _ in 0..MAX_EXACT_MATCH_TRIES {
+            let selected : Vec<usize> = added.iter().map(|d| order[*d]).collect();
+            let current = total(available, &selected)?;
+            let inputs = refs(available, &selected);
+            let fee_no_change = fee(&inputs, false)?;
+            let target = add(outputs, fee_no_change)?;
+            let tolerance = fee(&inputs, true)?.saturating_sub(fee_no_change);
+            if current >= target && current - target <= tolerance {
+                return Ok(Some(Selection { inputs: selected, fee: current - outputs, change: 0 }));
+            }
+            // go deeper unless over the target (adding more inputs only
+            // goes further) or the inputs left cannot reach it
+            if current < target && depth < order.len() && add(current, left[depth])? >= target {
+                added.push(depth);
+                depth += 1;
+                continue;
+            }
+            // otherwise leave out the last input added and go on from there
+            match added.pop() {
+                Some(last) => depth = last + 1,
+                None => return Ok(None)
+            }
+        }
+        Ok(None)
+    }
+
+    /// select the inputs to pay the given outputs (values in lovelace)
+    pub fn select(policy: &Policy, available: &[tx::Input], outputs: &[u64], fee: Fee) -> Result<Selection> {
+        let total_outputs = sum(outputs.iter().cloned())?;
+        match policy {
+            &Policy::LargestFirst => {
+                let mut order : Vec<usize> = (0..available.len()).collect();
+                order.sort_by(|a, b| value(&available[*b]).cmp(&value(&available[*a])));
+                in_order(available, order, total_outputs, fee)
+            },
+            &Policy::FirstInFirstOut => in_order(available, (0..available.len()).collect(), total_outputs, fee),
+            &Policy::RandomImprove { seed } => random_improve(available, outputs, seed, fee),
+            &Policy::ExactMatch => exact_match(available, total_outputs, fee),
+        }
+    }
+}
+
+/// the type of the witnesses of a transaction, to estimate its size
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+enum WitnessType {
+    PkWitness,
+    RedeemWitness
+}
+impl WitnessType {
+    /// a witness of this type signing the given transaction with a dummy
+    /// key: the keys and the signatures have a fixed size, so does the
+    /// CBOR of the witnesses of a type
+    fn dummy(self, tx: &tx::Tx) -> Result<tx::TxInWitness> {
+        let cfg = Config::default();
+        match self {
+            WitnessType::PkWitness => {
+                let seed = hdwallet::Seed::from_slice(&[0; hdwallet::SEED_SIZE])?;
+                Ok(tx::TxInWitness::new(&cfg, &hdwallet::XPrv::generate_from_seed(&seed), tx))
+            },
+            WitnessType::RedeemWitness => {
+                let key = redeem::PrivateKey::from_slice(&[0; redeem::PRIVATEKEY_SIZE])?;
+                Ok(tx::TxInWitness::new_redeem(&cfg, &key, tx))
+            }
+        }
+    }
+}
+
+/// size of the CBOR of the transaction once signed with the given witnesses
+fn signed_tx_size(tx: &tx::Tx, witnesses: &[WitnessType]) -> Result<usize> {
+    let mut dummies = BTreeMap::new();
+    for witness in witnesses {
+        if !dummies.contains_key(witness) { dummies.insert(*witness, witness.dummy(tx)?); }
+    }
+    let witnesses = witnesses.iter().map(|witness| dummies[witness].clone()).collect();
+    Ok(encode_to_cbor(&tx::TxAux::new(tx.clone(), witnesses))?.len())
+}
+
+/// the change value used to estimate the fee, the largest possible coin
+/// value so the estimation is never too low
+const MAX_COIN : u64 = 45_000_000_000_000_000;
+
+fn build_tx(inputs: &[&tx::Input], outputs: &[tx::TxOut], change: Option<(&address::ExtendedAddr, coin::Coin)>) -> tx::Tx {
+    let mut tx = tx::Tx::new();
+    for input in inputs { tx.add_input(input.ptr.clone()); }
+    for output in outputs { tx.add_output(output.clone()); }
+    if let Some((addr, value)) = change { tx.add_output(tx::TxOut::new(addr.clone(), value)); }
+    tx
+}
+
+/// estimate the fee of the transaction once signed (with a `PkWitness`
+/// for every input)
+fn tx_fee(fee_alg: &tx::fee::LinearFee, tx: &tx::Tx) -> Result<u64> {
+    let witnesses : Vec<WitnessType> = tx.inputs.iter().map(|_| WitnessType::PkWitness).collect();
+    let size = signed_tx_size(tx, &witnesses)?;
+    let fee = fee_alg.estimate(size)?;
+    Ok(u64::from(fee.to_coin()))
+}
+
+/// estimate the fee of the transactions spending some of the available
+/// inputs, as often as the selection of the inputs needs it.
+///
+/// The inputs are encoded one after the other in the transaction and every
+/// witness has the same size, so the size of the signed transaction is the
+/// size of the inputs plus a size depending on their number only (and on
+/// the change output). The latter is computed once for every number of
+/// inputs, from a transaction of as many copies of a reference input.
+struct FeeEstimator<'a> {
+    fee_alg: tx::fee::LinearFee,
+    outputs: &'a [tx::TxOut],
+    change_addr: &'a address::ExtendedAddr,
+    /// the size of the signed transactions but their inputs, by number of
+    /// inputs and change output
+    sizes: RefCell<BTreeMap<(usize, bool), usize>>
+}
+impl<'a> FeeEstimator<'a> {
+    fn new(fee_alg: tx::fee::LinearFee, outputs: &'a [tx::TxOut], change_addr: &'a address::ExtendedAddr) -> Self {
+        FeeEstimator { fee_alg: fee_alg, outputs: outputs, change_addr: change_addr, sizes: RefCell::new(BTreeMap::new()) }
+    }
+
+    fn size_without_inputs(&self, count: usize, change: bool) -> Result<usize> {
+        if let Some(size) = self.sizes.borrow().get(&(count, change)) {
+            return Ok(*size);
+        }
+        let reference = tx::TxIn::new(tx::TxId::from_slice(&[0; HASH_SIZE])?, 0);
+        let mut tx = tx::Tx::new();
+        for _ in 0..count { tx.add_input(reference.clone()); }
+        for output in self.outputs { tx.add_output(output.clone()); }
+        if change { tx.add_output(tx::TxOut::new(self.change_addr.clone(), coin::Coin::new(MAX_COIN)?)); }
+        let witnesses : Vec<WitnessType> = (0..count).map(|_| WitnessType::PkWitness).collect();
+        let size = signed_tx_size(&tx, &witnesses)? - count * encode_to_cbor(&reference)?.len();
+        self.sizes.borrow_mut().insert((count, change), size);
+        Ok(size)
+    }
+
+    /// the fee of the transaction spending the given inputs, with a
+    /// `PkWitness` for every input
+    fn fee(&self, inputs: &[&tx::Input], change: bool) -> Result<u64> {
+        let mut size = self.size_without_inputs(inputs.len(), change)?;
+        for input in inputs { size += encode_to_cbor(&input.ptr)?.len(); }
+        Ok(u64::from(self.fee_alg.estimate(size)?.to_coin()))
+    }
+}
+
+/// sign the transaction with the keys of the given inputs (in the order of
+/// the inputs of the transaction)
+fn sign_tx(wallet: &Wallet, cfg: &TxConfig, inputs: &[&tx::Input], tx: tx::Tx) -> tx::TxAux {
+    let witnesses = inputs.iter().map(|input| {
+        let xprv = secret::Secret::new(wallet.get_xprv(&input.addressing));
+        tx::TxInWitness::new(&cfg.config, &xprv, &tx)
+    }).collect();
+    tx::TxAux::new(tx, witnesses)
+}
+
+/// a transaction built with a selection policy, not signed yet
+struct UnsignedSpend {
+    tx: tx::Tx,
+    /// the spent inputs, in the order of the inputs of the transaction
+    inputs: Vec<tx::Input>,
+    fee: u64,
+    /// the value of the change output (the last output), 0 if none
+    change: u64
+}
+
+/// build the transaction with the inputs selected with the given policy
+fn build_with_policy(cfg: &TxConfig, policy: &coin_selection::Policy, inputs: &tx::Inputs, outputs: &tx::Outputs, change_addr: &address::ExtendedAddr) -> Result<UnsignedSpend> {
+    let available : Vec<tx::Input> = inputs.iter().cloned().collect();
+    let outputs : Vec<tx::TxOut> = outputs.iter().cloned().collect();
+    let values : Vec<u64> = outputs.iter().map(|output| u64::from(output.value)).collect();
+    let estimator = FeeEstimator::new(cfg.linear_fee(), &outputs, change_addr);
+    let fee = |inputs: &[&tx::Input], change: bool| estimator.fee(inputs, change);
+    let selection = coin_selection::select(policy, &available, &values, &fee)?;
+
+    let selected : Vec<&tx::Input> = selection.inputs.iter().map(|i| &available[*i]).collect();
+    let change = if selection.change > 0 {
+        Some((change_addr, coin::Coin::new(selection.change)?))
+    } else {
+        None
+    };
+    Ok(UnsignedSpend {
+        tx: build_tx(&selected, &outputs, change),
+        inputs: selected.into_iter().cloned().collect(),
+        fee: selection.fee,
+        change: selection.change
+    })
+}
+
+/// build and sign the transaction with the inputs selected with the given
+/// policy
+fn spend_with_policy(wallet: &Wallet, cfg: &TxConfig, policy: &coin_selection::Policy, inputs: &tx::Inputs, outputs: &tx::Outputs, change_addr: &address::ExtendedAddr) -> Result<WalletSpendOutput> {
+    let unsigned = build_with_policy(cfg, policy, inputs, outputs, change_addr)?;
+    let txaux = {
+        let selected : Vec<&tx::Input> = unsigned.inputs.iter().collect();
+        sign_tx(wallet, cfg, &selected, unsigned.tx)
+    };
+    Ok(WalletSpendOutput {
+        cbor_encoded_tx: encode_to_cbor(&txaux)?,
+        tx: txaux,
+        fee: tx::fee::Fee::new(coin::Coin::new(unsigned.fee)?),
+        inputs: unsigned.inputs
+    })
+}
+
+/// build and sign the transaction with the given policy or, without
+/// policy, with the default selection of the wallet. The transaction is
+/// signed with the configuration of the wallet in both cases.
+fn wallet_spend(wallet: &Wallet, linear_fee: Option<LinearFeeCoefficients>, policy: Option<&coin_selection::Policy>, inputs: &tx::Inputs, outputs: &tx::Outputs, change_addr: &address::ExtendedAddr) -> Result<WalletSpendOutput> {
+    if let Some(policy) = policy {
+        let cfg = TxConfig::of_wallet(wallet, linear_fee);
+        return spend_with_policy(wallet, &cfg, policy, inputs, outputs, change_addr);
+    }
+    let (txaux, fee) = wallet.new_transaction(inputs, outputs, change_addr)?;
+    let spent = inputs.iter().filter(|input| txaux.tx.inputs.contains(&input.ptr)).cloned().collect();
+    Ok(WalletSpendOutput {
+        cbor_encoded_tx: encode_to_cbor(&txaux)?,
+        tx: txaux,
+        fee: fee,
+        inputs: spent
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn xwallet_spend(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : WalletSpendInput = input_secret_json!(output_ptr, input_ptr, input_sz);
+    let output = jrpc_try!(output_ptr, wallet_spend(&input.wallet, input.linear_fee, input.selection_policy.as_ref(), &input.inputs, &input.outputs, &input.change_addr));
+    jrpc_ok!(output_ptr, output)
+}
+
+/// build and sign the transaction sending all the inputs (minus the fee)
+/// to the destination, without change. The transaction is signed with the
+/// configuration of the wallet.
+fn wallet_sweep(wallet: &Wallet, linear_fee: Option<LinearFeeCoefficients>, inputs: &tx::Inputs, destination: &address::ExtendedAddr) -> Result<WalletSpendOutput> {
+    let cfg = TxConfig::of_wallet(wallet, linear_fee);
+    let spent : Vec<&tx::Input> = inputs.iter().collect();
+    let balance = spent.iter().fold(Some(0u64), |sum, input| {
+        sum.and_then(|sum| sum.checked_add(u64::from(input.value.value)))
+    });
+    let balance = match balance {
+        Some(balance) => balance,
+        None => return Err(Error::ErrorCoinSelection("the value of the inputs overflows".to_string()))
+    };
+
+    // the fee depends on the size of the output value: start with no fee
+    // and grow it until it covers the fee of the transaction
+    let fee_alg = cfg.linear_fee();
+    let mut fee = 0;
+    loop {
+        if balance <= fee {
+            return Err(Error::ErrorBalanceBelowFee(balance, fee));
+        }
+        let output = tx::TxOut::new(destination.clone(), coin::Coin::new(balance - fee)?);
+        let tx = build_tx(&spent, &[output], None);
+        let needed = tx_fee(&fee_alg, &tx)?;
+        if needed <= fee {
+            let txaux = sign_tx(wallet, &cfg, &spent, tx);
+            return Ok(WalletSpendOutput {
+                cbor_encoded_tx: encode_to_cbor(&txaux)?,
+                tx: txaux,
+                fee: tx::fee::Fee::new(coin::Coin::new(fee)?),
+                inputs: spent.into_iter().cloned().collect()
+            });
+        }
+        fee = needed;
+    }
+}
+
+#[derive(Deserialize)]
+struct WalletSweepInput {
+    wallet: secret::Secret<Wallet>,
+    inputs: tx::Inputs,
+    destination: address::ExtendedAddr,
+    /// the linear fee coefficients, the ones of the network by default
+    #[serde(default)]
+    linear_fee: Option<LinearFeeCoefficients>
+}
+
+/// send all the given inputs to the destination address, the fee being
+/// taken from the sent value. Fails if the inputs don't cover the fee.
+#[no_mangle]
+pub extern "C" fn xwallet_sweep(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : WalletSweepInput = input_secret_json!(output_ptr, input_ptr, input_sz);
+    let output = jrpc_try!(output_ptr, wallet_sweep(&input.wallet, input.linear_fee, &input.inputs, &input.destination));
+    jrpc_ok!(output_ptr, output)
+}
+
+/// the change output of an unsigned transaction and where its key is in
+/// the wallet
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct EnvelopeChange {
+    address: address::ExtendedAddr,
+    value: u64,
+    addressing: bip44::Addressing
+}
+
+/// an unsigned transaction with everything the signer needs to check it
+/// before signing it, without access to the network
+#[derive(Serialize, Deserialize, Debug)]
+struct UnsignedTxEnvelope {
+    /// the configuration (protocol magic) to sign the transaction with
+    config: Config,
+    /// the CBOR of the unsigned transaction
+    tx: Vec<u8>,
+    /// the spent inputs (values and addressing of their keys), in the order
+    /// of the inputs of the transaction
+    inputs: Vec<tx::Input>,
+    change: Option<EnvelopeChange>,
+    /// the fee (lovelace)
+    fee: u64
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct PrepareTxInput {
+    #[serde(default)]
+    config: TxConfig,
+    inputs: tx::Inputs,
+    outputs: tx::Outputs,
+    change_addr: address::ExtendedAddr,
+    /// where the key of the change address is in the wallet
+    change_addressing: bip44::Addressing,
+    /// the input selection, first in first out by default
+    #[serde(default)]
+    selection_policy: Option<coin_selection::Policy>
+}
+
+fn prepare_tx(input: PrepareTxInput) -> Result<UnsignedTxEnvelope> {
+    let policy = input.selection_policy.unwrap_or(coin_selection::Policy::FirstInFirstOut);
+    let unsigned = build_with_policy(&input.config, &policy, &input.inputs, &input.outputs, &input.change_addr)?;
+    let change = if unsigned.change > 0 {
+        Some(EnvelopeChange { address: input.change_addr, value: unsigned.change, addressing: input.change_addressing })
+    } else {
+        None
+    };
+    Ok(UnsignedTxEnvelope {
+        config: input.config.config,
+        tx: encode_to_cbor(&unsigned.tx)?,
+        inputs: unsigned.inputs,
+        change: change,
+        fee: unsigned.fee
+    })
+}
+
+/// build the unsigned transaction envelope, without the private keys: the
+/// inputs only need the addressing of their keys in the wallet
+#[no_mangle]
+pub extern "C" fn xtx_prepare(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : PrepareTxInput = input_json!(output_ptr, input_ptr, input_sz);
+    let envelope = jrpc_try!(output_ptr, prepare_tx(input));
+    jrpc_ok!(output_ptr, envelope)
+}
+
+/// check the envelope is for the network of the wallet, describes its
+/// transaction and the spent and change addresses are the ones of the wallet
+fn check_envelope(wallet: &Wallet, envelope: &UnsignedTxEnvelope, tx: &tx::Tx) -> Result<()> {
+    let invalid = |msg: &str| Err(Error::ErrorInvalidEnvelope(msg.to_string()));
+    if envelope.config.protocol_magic != wallet.config.protocol_magic {
+        return invalid("the protocol magic is not the one of the wallet");
+    }
+    if tx.inputs.len() != envelope.inputs.len() {
+        return invalid("the inputs are not the inputs of the transaction");
+    }
+    for (txin, input) in tx.inputs.iter().zip(envelope.inputs.iter()) {
+        if txin != &input.ptr {
+            return invalid("the inputs are not the inputs of the transaction");
+        }
+        let xprv = secret::Secret::new(wallet.get_xprv(&input.addressing));
+        if ! is_address_of(&xprv.public(), &input.value.address) {
+            return invalid("an input is not an input of the wallet");
+        }
+    }
+    if let Some(ref change) = envelope.change {
+        let is_change = match tx.outputs.last() {
+            Some(output) => output.address == change.address && u64::from(output.value) == change.value,
+            None => false
+        };
+        if ! is_change {
+            return invalid("the change is not the last output of the transaction");
+        }
+        let xprv = secret::Secret::new(wallet.get_xprv(&change.addressing));
+        if ! is_address_of(&xprv.public(), &change.address) {
+            return invalid("the change address is not an address of the wallet");
+        }
+    }
+    let inputs = sum_values(envelope.inputs.iter().map(|input| &input.value));
+    let outputs = sum_values(tx.outputs.iter());
+    match (inputs, outputs) {
+        (Some(inputs), Some(outputs)) if inputs >= outputs && inputs - outputs == envelope.fee => Ok(()),
+        _ => invalid("the fee is not the difference between the inputs and the outputs")
+    }
+}
+
+#[derive(Deserialize)]
+struct SignEnvelopeInput {
+    wallet: secret::Secret<Wallet>,
+    envelope: UnsignedTxEnvelope
+}
+
+/// check and sign the unsigned transaction envelope built by `xtx_prepare`,
+/// returns the signed transaction (`TxAux`) like `xtx_sign`
+#[no_mangle]
+pub extern "C" fn xtx_sign_envelope(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : SignEnvelopeInput = input_secret_json!(output_ptr, input_ptr, input_sz);
+    let tx : tx::Tx = jrpc_try!(output_ptr, decode_from_cbor(&input.envelope.tx));
+    jrpc_try!(output_ptr, check_envelope(&input.wallet, &input.envelope, &tx));
+    let id = hex::encode(tx.id().as_ref());
+    let cfg = TxConfig::of_wallet(&input.wallet, None);
+    let txaux = {
+        let inputs : Vec<&tx::Input> = input.envelope.inputs.iter().collect();
+        sign_tx(&input.wallet, &cfg, &inputs, tx)
+    };
+    let cbor = jrpc_try!(output_ptr, encode_to_cbor(&txaux));
+    jrpc_ok!(output_ptr, TxSignOutput { id: id, cbor_encoded_tx: cbor })
+}
+
+#[derive(Deserialize)]
+struct CreateWalletAccount {
+    wallet: secret::Secret<Wallet>,
+    account: u32
+}
+
+#[no_mangle]
+pub extern "C" fn xwallet_account(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : CreateWalletAccount = input_secret_json!(output_ptr, input_ptr, input_sz);
+    jrpc_ok!(
+        output_ptr,
+        input.wallet.account(input.account)
+    )
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct GenAddressesInput {
+    account: Account,
+    derivation_scheme: hdwallet::DerivationScheme,
+    address_type: bip44::AddrType,
+    indices: Vec<u32>
+}
+
+#[no_mangle]
+pub extern "C" fn xwallet_addresses(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : GenAddressesInput = input_json!(output_ptr, input_ptr, input_sz);
+    jrpc_try!(output_ptr, check_derivation_scheme(input.account.derivation_scheme(), input.derivation_scheme));
+    let addresses : Vec<address::ExtendedAddr> =
+        jrpc_try!(output_ptr, input.account.gen_addresses(input.address_type, input.indices));
     jrpc_ok!(
         output_ptr,
         addresses
     )
 }
 
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct AccountFromXPubInput {
+    /// the account public key (64 bytes)
+    xpub: Vec<u8>,
+    derivation_scheme: hdwallet::DerivationScheme
+}
+
+/// create a watch-only account from the account public key (the key at
+/// `m/44'/1815'/account'`): it generates the addresses of the account
+/// without any private key
+#[no_mangle]
+pub extern "C" fn xwallet_account_from_xpub(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : AccountFromXPubInput = input_json!(output_ptr, input_ptr, input_sz);
+    let xpub = jrpc_try!(output_ptr, hdwallet::XPub::from_slice(&input.xpub));
+    jrpc_ok!(output_ptr, Account::new(xpub, input.derivation_scheme))
+}
+
+/// default number of addresses of each chain searched by
+/// `xwallet_account_recognise`
+fn default_recognise_limit() -> u32 { 1000 }
+
+/// maximum number of addresses of each chain searched, they are all
+/// generated at once
+const MAX_RECOGNISE_LIMIT : u32 = 100_000;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct RecogniseAddressesInput {
+    account: Account,
+    derivation_scheme: hdwallet::DerivationScheme,
+    addresses: Vec<address::ExtendedAddr>,
+    /// the addresses of each chain searched: indices `0` to `limit - 1`
+    #[serde(default = "default_recognise_limit")]
+    limit: u32
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct RecognisedAddress {
+    address: address::ExtendedAddr,
+    address_type: bip44::AddrType,
+    index: u32
+}
+
+fn recognise_addresses(account: &Account, addresses: Vec<address::ExtendedAddr>, limit: u32) -> Result<Vec<RecognisedAddress>> {
+    let mut known = BTreeMap::new();
+    for address_type in vec![bip44::AddrType::External, bip44::AddrType::Internal] {
+        let generated = account.gen_addresses(address_type.clone(), (0..limit).collect())?;
+        for (index, address) in (0..limit).zip(generated.into_iter()) {
+            known.entry(address.to_bytes()).or_insert((address_type.clone(), index));
+        }
+    }
+    Ok(addresses.into_iter().filter_map(|address| {
+        let found = known.get(&address.to_bytes()).cloned();
+        found.map(|(address_type, index)| RecognisedAddress { address: address, address_type: address_type, index: index })
+    }).collect())
+}
+
+/// find, among the given addresses, the ones of the account and their
+/// chain and index
+#[no_mangle]
+pub extern "C" fn xwallet_account_recognise(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : RecogniseAddressesInput = input_json!(output_ptr, input_ptr, input_sz);
+    jrpc_try!(output_ptr, check_derivation_scheme(input.account.derivation_scheme(), input.derivation_scheme));
+    if input.limit > MAX_RECOGNISE_LIMIT {
+        return jrpc_fail!(output_ptr, "at most {} addresses of each chain can be searched", MAX_RECOGNISE_LIMIT);
+    }
+    let recognised = jrpc_try!(output_ptr, recognise_addresses(&input.account, input.addresses, input.limit));
+    jrpc_ok!(output_ptr, recognised)
+}
+
+/// default number of consecutive unused addresses ending a chain
+fn default_gap_limit() -> u32 { 20 }
+
+/// maximum gap limit, the addresses are generated `gap_limit` at a time
+const MAX_GAP_LIMIT : u32 = 10_000;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct DiscoverAddressesInput {
+    account: Account,
+    derivation_scheme: hdwallet::DerivationScheme,
+    /// the addresses known to be used (with transactions on the chain)
+    used: Vec<address::ExtendedAddr>,
+    #[serde(default = "default_gap_limit")]
+    gap_limit: u32
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct UsedAddress {
+    address: address::ExtendedAddr,
+    index: u32
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct DiscoveredChain {
+    used: Vec<UsedAddress>,
+    /// the index following the last used address
+    next_index: u32
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct DiscoverAddressesOutput {
+    external: DiscoveredChain,
+    internal: DiscoveredChain
+}
+
+/// walk the chain until `gap_limit` consecutive addresses are not used
+fn discover_chain(account: &Account, address_type: bip44::AddrType, used: &BTreeSet<Vec<u8>>, gap_limit: u32) -> Result<DiscoveredChain> {
+    let mut chain = DiscoveredChain { used: Vec::new(), next_index: 0 };
+    let mut start = 0;
+    loop {
+        // the addresses indices are not hardened: the chain stops there
+        let end = match chain.next_index.checked_add(gap_limit) {
+            Some(end) if end < HARDENED => end,
+            _ => HARDENED
+        };
+        if start >= end { break; }
+        let addresses = account.gen_addresses(address_type.clone(), (start..end).collect())?;
+        for (index, address) in (start..end).zip(addresses.into_iter()) {
+            if used.contains(&address.to_bytes()) {
+                chain.used.push(UsedAddress { address: address, index: index });
+                chain.next_index = index + 1;
+            }
+        }
+        start = end;
+    }
+    Ok(chain)
+}
+
+/// find the used addresses of the external and internal chains of the
+/// account, each chain ending with `gap_limit` (20 by default) unused
+/// addresses, and the next unused index of each chain
+#[no_mangle]
+pub extern "C" fn xwallet_account_discover(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : DiscoverAddressesInput = input_json!(output_ptr, input_ptr, input_sz);
+    jrpc_try!(output_ptr, check_derivation_scheme(input.account.derivation_scheme(), input.derivation_scheme));
+    if input.gap_limit == 0 || input.gap_limit > MAX_GAP_LIMIT {
+        return jrpc_fail!(output_ptr, "the gap limit must be between 1 and {}", MAX_GAP_LIMIT);
+    }
+    let used = input.used.iter().map(|address| address.to_bytes()).collect();
+    let external = jrpc_try!(output_ptr, discover_chain(&input.account, bip44::AddrType::External, &used, input.gap_limit));
+    let internal = jrpc_try!(output_ptr, discover_chain(&input.account, bip44::AddrType::Internal, &used, input.gap_limit));
+    jrpc_ok!(output_ptr, DiscoverAddressesOutput { external: external, internal: internal })
+}
+
+#[derive(Deserialize)]
+struct DecryptAddressesInput {
+    /// the payload key (see `wallet_payload_initiate`)
+    key: Vec<u8>,
+    /// the addresses (CBOR) to try to decrypt the payload of
+    addresses: Vec<Vec<u8>>
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct DecryptedAddress {
+    /// the position of the address in the input
+    index: usize,
+    path: Vec<u32>
+}
+
+/// try to decrypt the derivation path of every address with the payload
+/// key, returns the paths of the addresses that could be decrypted only
+/// (the addresses that cannot be decoded are left out too)
+#[no_mangle]
+pub extern "C" fn xpayload_decrypt_addresses(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : DecryptAddressesInput = input_json!(output_ptr, input_ptr, input_sz);
+    let hdkey = jrpc_try!(output_ptr, hdpayload::HDKey::from_slice(&input.key).ok_or(Error::ErrorInvalidHDKey));
+    let mut decrypted = Vec::new();
+    for (index, bytes) in input.addresses.iter().enumerate() {
+        let addr = match address::ExtendedAddr::from_bytes(bytes) {
+            Ok(addr) => addr,
+            Err(_) => continue
+        };
+        let path = addr.attributes.derivation_path.as_ref().and_then(|payload| hdkey.decrypt_path(payload));
+        if let Some(path) = path {
+            decrypted.push(DecryptedAddress { index: index, path: path.as_ref().to_vec() });
+        }
+    }
+    jrpc_ok!(output_ptr, decrypted)
+}
+
+/// the address to inspect, in base58 or in CBOR
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum InspectAddressInput {
+    Base58(String),
+    Cbor(Vec<u8>)
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum InspectedStakeDistribution {
+    BootstrapEra,
+    SingleKey { stakeholder_id: String }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct InspectedPayload {
+    length: usize
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct InspectAddressOutput {
+    address: String,
+    /// the hash of the spending data and the attributes (hex)
+    root: String,
+    /// `pubkey`, `script` or `redeem`
+    address_type: String,
+    /// the kind of spending data hashed in the root: `public_key`,
+    /// `script` or `redeem_public_key`. The spending data itself is not in
+    /// the address, only its hash, but in the bootstrap era the address
+    /// type tells its kind.
+    spending_data: String,
+    stake_distribution: InspectedStakeDistribution,
+    network_magic: Option<u32>,
+    /// the encrypted derivation path, if any
+    payload: Option<InspectedPayload>
+}
+impl<'a> From<&'a address::ExtendedAddr> for InspectAddressOutput {
+    fn from(ea: &'a address::ExtendedAddr) -> Self {
+        let (address_type, spending_data) = match ea.addr_type {
+            address::AddrType::ATPubKey => ("pubkey", "public_key"),
+            address::AddrType::ATScript => ("script", "script"),
+            address::AddrType::ATRedeem => ("redeem", "redeem_public_key"),
+        };
+        let stake_distribution = match ea.attributes.stake_distribution {
+            address::StakeDistribution::BootstrapEraDistr => InspectedStakeDistribution::BootstrapEra,
+            address::StakeDistribution::SingleKeyDistr(ref id) => InspectedStakeDistribution::SingleKey {
+                stakeholder_id: hex::encode(id.as_ref())
+            },
+        };
+        let network_magic = match ea.attributes.network_magic {
+            address::NetworkMagic::NoMagic  => None,
+            address::NetworkMagic::Magic(m) => Some(m),
+        };
+        InspectAddressOutput {
+            address: format!("{}", ea),
+            root: hex::encode(ea.addr.as_ref()),
+            address_type: address_type.to_string(),
+            spending_data: spending_data.to_string(),
+            stake_distribution: stake_distribution,
+            network_magic: network_magic,
+            payload: ea.attributes.derivation_path.as_ref().map(|payload| InspectedPayload { length: payload.as_ref().len() })
+        }
+    }
+}
+
+/// decode the address and return its details, or the reason the address
+/// is not valid
+#[no_mangle]
+pub extern "C" fn xaddress_inspect(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : InspectAddressInput = input_json!(output_ptr, input_ptr, input_sz);
+    let ea = match input {
+        InspectAddressInput::Base58(address) => jrpc_try!(output_ptr, address_from_base58(&address)),
+        InspectAddressInput::Cbor(bytes)     => jrpc_try!(output_ptr, address::ExtendedAddr::from_bytes(&bytes)),
+    };
+    jrpc_ok!(output_ptr, InspectAddressOutput::from(&ea))
+}
+
 #[no_mangle]
-pub extern "C" fn xwallet_checkaddress(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut c_uchar) -> i32 {
+pub extern "C" fn xwallet_checkaddress(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
     let input : String = input_json!(output_ptr, input_ptr, input_sz);
     let bytes : Vec<u8> = jrpc_try!(output_ptr, hex::decode(&input));
     let _ : address::ExtendedAddr = jrpc_try!(output_ptr, cbor::decode_from_cbor(&bytes));
     jrpc_ok!(output_ptr, true)
 }
+
+/// a transaction input, as displayed by `xtx_decode`
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct DecodedTxIn {
+    id: String,
+    index: u32
+}
+impl<'a> From<&'a tx::TxIn> for DecodedTxIn {
+    fn from(txin: &'a tx::TxIn) -> Self {
+        DecodedTxIn { id: hex::encode(txin.id.as_ref()), index: txin.index }
+    }
+}
+
+/// a transaction output, as displayed by `xtx_decode`: the address in
+/// base58 and the value in lovelace (a string as it may not fit in a
+/// javascript number)
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct DecodedTxOut {
+    address: String,
+    value: String,
+    /// the encrypted derivation path of the address (hex), if any
+    payload: Option<String>
+}
+impl<'a> From<&'a tx::TxOut> for DecodedTxOut {
+    fn from(txout: &'a tx::TxOut) -> Self {
+        DecodedTxOut {
+            address: format!("{}", txout.address),
+            value: u64::from(txout.value).to_string(),
+            payload: txout.address.attributes.derivation_path.as_ref().map(|payload| hex::encode(payload.as_ref()))
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(tag = "type")]
+enum DecodedWitness {
+    PkWitness { public_key: String, signature: String },
+    RedeemWitness { public_key: String, signature: String },
+    /// the scripts in hex
+    ScriptWitness { validator: String, redeemer: String },
+    /// the other witnesses are given in CBOR (hex)
+    Other { cbor: String }
+}
+impl DecodedWitness {
+    fn new(witness: &tx::TxInWitness) -> Result<Self> {
+        match witness {
+            &tx::TxInWitness::PkWitness(ref xpub, ref signature) => Ok(DecodedWitness::PkWitness {
+                public_key: hex::encode(xpub.as_ref()),
+                signature: hex::encode(signature.as_ref())
+            }),
+            &tx::TxInWitness::RedeemWitness(ref key, ref signature) => Ok(DecodedWitness::RedeemWitness {
+                public_key: hex::encode(key.as_ref()),
+                signature: hex::encode(signature.as_ref())
+            }),
+            &tx::TxInWitness::ScriptWitness(ref validator, ref redeemer) => Ok(DecodedWitness::ScriptWitness {
+                validator: hex::encode(&encode_to_cbor(validator)?),
+                redeemer: hex::encode(&encode_to_cbor(redeemer)?)
+            }),
+            witness => Ok(DecodedWitness::Other { cbor: hex::encode(&encode_to_cbor(witness)?) })
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct DecodedTx {
+    id: String,
+    inputs: Vec<DecodedTxIn>,
+    outputs: Vec<DecodedTxOut>,
+    /// the CBOR (hex) of the transaction attributes, an empty map (`a0`)
+    /// in the bootstrap era
+    attributes: String,
+    /// the witnesses of the inputs if the transaction is signed (`TxAux`)
+    witnesses: Option<Vec<DecodedWitness>>
+}
+impl DecodedTx {
+    fn new(tx: &tx::Tx, attributes: String, witnesses: Option<&[tx::TxInWitness]>) -> Result<Self> {
+        let witnesses = match witnesses {
+            None => None,
+            Some(witnesses) => Some(witnesses.iter().map(DecodedWitness::new).collect::<Result<Vec<_>>>()?)
+        };
+        Ok(DecodedTx {
+            id: hex::encode(tx.id().as_ref()),
+            inputs: tx.inputs.iter().map(DecodedTxIn::from).collect(),
+            outputs: tx.outputs.iter().map(DecodedTxOut::from).collect(),
+            attributes: attributes,
+            witnesses: witnesses
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(tag = "type")]
+enum Decoded {
+    TxAux(DecodedTx),
+    Tx(DecodedTx),
+    TxIn(DecodedTxIn),
+    TxOut(DecodedTxOut)
+}
+
+/// the CBOR (hex) of the attributes of the transaction, the last item of
+/// its array (`tx::Tx` does not keep them once decoded)
+fn tx_attributes(tx: &cbor::Value) -> Result<String> {
+    match tx {
+        &cbor::Value::Array(ref items) if items.len() == 3 => Ok(hex::encode(&encode_to_cbor(&items[2])?)),
+        _ => Err(Error::ErrorInvalidCbor("the transaction is not an array of 3 items".to_string()))
+    }
+}
+
+fn decode_tx_cbor(bytes: &[u8]) -> Result<Decoded> {
+    let txaux_err = match decode_from_cbor::<tx::TxAux>(bytes) {
+        Ok(txaux) => {
+            let attributes = match decode_from_cbor::<cbor::Value>(bytes)? {
+                cbor::Value::Array(ref items) if items.len() == 2 => tx_attributes(&items[0])?,
+                _ => return Err(Error::ErrorInvalidCbor("the signed transaction is not an array of 2 items".to_string()))
+            };
+            return Ok(Decoded::TxAux(DecodedTx::new(&txaux.tx, attributes, Some(&txaux.witnesses))?));
+        },
+        Err(err) => err
+    };
+    let tx_err = match decode_from_cbor::<tx::Tx>(bytes) {
+        Ok(tx) => {
+            let attributes = tx_attributes(&decode_from_cbor(bytes)?)?;
+            return Ok(Decoded::Tx(DecodedTx::new(&tx, attributes, None)?));
+        },
+        Err(err) => err
+    };
+    let txin_err = match decode_from_cbor::<tx::TxIn>(bytes) {
+        Ok(txin) => return Ok(Decoded::TxIn(DecodedTxIn::from(&txin))),
+        Err(err) => err
+    };
+    match decode_from_cbor::<tx::TxOut>(bytes) {
+        Ok(txout) => Ok(Decoded::TxOut(DecodedTxOut::from(&txout))),
+        Err(txout_err) => Err(Error::ErrorInvalidCbor(format!(
+            "not a TxAux ({:?}), a Tx ({:?}), a TxIn ({:?}) nor a TxOut ({:?})", txaux_err, tx_err, txin_err, txout_err
+        )))
+    }
+}
+
+/// decode the CBOR of a `TxAux` (signed transaction), a `Tx`, a `TxIn` or
+/// a `TxOut` (array of bytes) to a readable JSON object, its `type` field
+/// telling which one it was
+#[no_mangle]
+pub extern "C" fn xtx_decode(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : Vec<u8> = input_json!(output_ptr, input_ptr, input_sz);
+    let decoded = jrpc_try!(output_ptr, decode_tx_cbor(&input));
+    jrpc_ok!(output_ptr, decoded)
+}
+
+/// the private keys signing the inputs of a transaction
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum TxSigners {
+    /// the extended private keys (96 bytes), in the order of the inputs
+    Keys(Vec<Vec<u8>>),
+    /// the wallet and the BIP44 addressing of every input
+    Wallet { wallet: secret::Secret<Wallet>, addressing: Vec<bip44::Addressing> }
+}
+impl TxSigners {
+    fn len(&self) -> usize {
+        match self {
+            &TxSigners::Keys(ref keys) => keys.len(),
+            &TxSigners::Wallet { ref addressing, .. } => addressing.len()
+        }
+    }
+
+    fn xprvs(self) -> Result<Vec<secret::Secret<hdwallet::XPrv>>> {
+        match self {
+            TxSigners::Keys(keys) => keys.into_iter().map(|key| {
+                let key = secret::SecretBytes::new(key);
+                Ok(secret::Secret::new(hdwallet::XPrv::from_slice(&key)?))
+            }).collect(),
+            TxSigners::Wallet { wallet, addressing } => Ok(addressing.iter().map(|addressing| {
+                secret::Secret::new(wallet.get_xprv(addressing))
+            }).collect())
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TxSignInput {
+    config: Config,
+    /// the CBOR of the transaction to sign
+    tx: Vec<u8>,
+    signers: TxSigners
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct TxSignOutput {
+    id: String,
+    cbor_encoded_tx: Vec<u8>
+}
+
+/// sign every input of the given transaction and returns the signed
+/// transaction (`TxAux`) ready to be sent to the network
+#[no_mangle]
+pub extern "C" fn xtx_sign(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : TxSignInput = input_secret_json!(output_ptr, input_ptr, input_sz);
+    let tx : tx::Tx = jrpc_try!(output_ptr, decode_from_cbor(&input.tx));
+    if input.signers.len() != tx.inputs.len() {
+        return jrpc_fail!(output_ptr, "{} inputs but {} signers", tx.inputs.len(), input.signers.len());
+    }
+    let xprvs = jrpc_try!(output_ptr, input.signers.xprvs());
+    let witnesses = xprvs.iter().map(|xprv| tx::TxInWitness::new(&input.config, xprv, &tx)).collect();
+    let id = hex::encode(tx.id().as_ref());
+    let txaux = tx::TxAux::new(tx, witnesses);
+    let cbor = jrpc_try!(output_ptr, encode_to_cbor(&txaux));
+    jrpc_ok!(output_ptr, TxSignOutput { id: id, cbor_encoded_tx: cbor })
+}
+
+#[derive(Deserialize)]
+struct RedeemInput {
+    config: Config,
+    /// the redemption private key (32 bytes)
+    redemption_key: Vec<u8>,
+    /// the value of the certificate (in lovelace, a string of decimal
+    /// digits as it may not fit in a javascript number)
+    value: String,
+    destination: address::ExtendedAddr
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct RedeemOutput {
+    id: String,
+    cbor_encoded_tx: Vec<u8>
+}
+
+/// the input of the redeem address: the AVVM outputs are identified by
+/// the hash of their address
+fn redeem_txin(addr: &address::ExtendedAddr) -> tx::TxIn {
+    tx::TxIn::new(tx::TxId::new(&addr.to_bytes()), 0)
+}
+
+/// build and sign the transaction sending the whole value of the redeem
+/// address to the destination: the redemption transactions (spending redeem
+/// addresses only) are exempt from fees
+fn redeem_tx(cfg: &Config, key: &redeem::PrivateKey, value: coin::Coin, destination: &address::ExtendedAddr) -> Result<RedeemOutput> {
+    let mut tx = tx::Tx::new();
+    tx.add_input(redeem_txin(&redeem_address(&key.public())));
+    tx.add_output(tx::TxOut::new(destination.clone(), value));
+    let witness = tx::TxInWitness::new_redeem(cfg, key, &tx);
+    let id = hex::encode(tx.id().as_ref());
+    let txaux = tx::TxAux::new(tx, vec![witness]);
+    Ok(RedeemOutput { id: id, cbor_encoded_tx: encode_to_cbor(&txaux)? })
+}
+
+/// claim the value of an ADA vending certificate: returns the signed
+/// transaction spending the redeem address of the redemption key
+#[no_mangle]
+pub extern "C" fn xtx_redeem(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : RedeemInput = input_secret_json!(output_ptr, input_ptr, input_sz);
+    let key_bytes = secret::SecretBytes::new(input.redemption_key);
+    let key = secret::Secret::new(jrpc_try!(output_ptr, redeem::PrivateKey::from_slice(&key_bytes)));
+    let value = jrpc_try!(output_ptr, parse_amount(&input.value));
+    let output = jrpc_try!(output_ptr, redeem_tx(&input.config, &key, value, &input.destination));
+    jrpc_ok!(output_ptr, output)
+}
+
+/// a script of a script witness
+#[derive(Serialize, Deserialize)]
+struct ScriptInput {
+    version: u16,
+    script: Vec<u8>
+}
+impl ScriptInput {
+    fn to_script(self) -> address::Script { address::Script::new(self.version, self.script) }
+}
+
+/// a witness given to `xtx_assemble`
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum WitnessInput {
+    Pk { public_key: Vec<u8>, signature: Vec<u8> },
+    /// the validator is the script of the spent address, the redeemer the
+    /// script unlocking it
+    Script { validator: ScriptInput, redeemer: ScriptInput },
+    /// the validator of `xaddress_multisig` and the signatures of the
+    /// transaction (made with `wallet_tx_sign`), the redeemer is built from
+    /// the signatures
+    Multisig { validator: ScriptInput, signatures: Vec<MultisigSignature> }
+}
+impl WitnessInput {
+    fn to_witness(self) -> Result<tx::TxInWitness> {
+        match self {
+            WitnessInput::Pk { public_key, signature } => Ok(tx::TxInWitness::PkWitness(
+                hdwallet::XPub::from_slice(&public_key)?,
+                hdwallet::Signature::from_slice(&signature)?
+            )),
+            WitnessInput::Script { validator, redeemer } => Ok(tx::TxInWitness::ScriptWitness(
+                validator.to_script(),
+                redeemer.to_script()
+            )),
+            WitnessInput::Multisig { validator, signatures } => {
+                let validator = validator.to_script();
+                let multisig = Multisig::from_script(&validator)
+                    .ok_or_else(|| Error::ErrorMultisig("the validator is not a multisig script".to_string()))?;
+                let redeemer = multisig.redeemer(&signatures)?;
+                Ok(tx::TxInWitness::ScriptWitness(validator, redeemer))
+            },
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct AssembleTxInput {
+    /// the CBOR of the transaction
+    tx: Vec<u8>,
+    /// the witnesses, in the order of the inputs
+    witnesses: Vec<WitnessInput>
+}
+
+/// assemble the signed transaction (`TxAux`) from the transaction and the
+/// witnesses of its inputs, signatures made elsewhere or script witnesses
+/// of the inputs spending script addresses
+#[no_mangle]
+pub extern "C" fn xtx_assemble(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : AssembleTxInput = input_json!(output_ptr, input_ptr, input_sz);
+    let tx : tx::Tx = jrpc_try!(output_ptr, decode_from_cbor(&input.tx));
+    if input.witnesses.len() != tx.inputs.len() {
+        return jrpc_fail!(output_ptr, "{} inputs but {} witnesses", tx.inputs.len(), input.witnesses.len());
+    }
+    let witnesses = jrpc_try!(output_ptr, input.witnesses.into_iter().map(|witness| witness.to_witness()).collect::<Result<Vec<_>>>());
+    let id = hex::encode(tx.id().as_ref());
+    let txaux = tx::TxAux::new(tx, witnesses);
+    let cbor = jrpc_try!(output_ptr, encode_to_cbor(&txaux));
+    jrpc_ok!(output_ptr, TxSignOutput { id: id, cbor_encoded_tx: cbor })
+}
+
+/// the reasons why a signed transaction would be rejected, as reported by
+/// `xtx_verify`. The `index` fields are the indices of the inputs.
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(tag = "violation", rename_all = "snake_case")]
+enum TxViolation {
+    NoInputs,
+    NoOutputs,
+    DuplicateInput { index: usize },
+    WitnessCount { inputs: usize, witnesses: usize },
+    ResolvedCount { inputs: usize, resolved: usize },
+    /// the key (or the validator script) of the witness is not the one of
+    /// the spent address
+    WitnessAddressMismatch { index: usize },
+    InvalidSignature { index: usize },
+    /// the redeemer of a multisig validator does not hold enough valid
+    /// signatures
+    ScriptRejected { index: usize },
+    ValueOverflow,
+    /// the inputs don't cover the outputs and the minimum fee (lovelace)
+    InsufficientFee { inputs: String, outputs: String, fee: String }
+}
+
+fn sum_values<'a, I: Iterator<Item = &'a tx::TxOut>>(outputs: I) -> Option<u64> {
+    outputs.fold(Some(0), |sum, txout| sum.and_then(|sum| sum.checked_add(u64::from(txout.value))))
+}
+
+fn verify_txaux(cfg: &TxConfig, txaux: &tx::TxAux, resolved: &[tx::TxOut]) -> Result<Vec<TxViolation>> {
+    let tx = &txaux.tx;
+    let mut violations = Vec::new();
+
+    if tx.inputs.is_empty() { violations.push(TxViolation::NoInputs); }
+    if tx.outputs.is_empty() { violations.push(TxViolation::NoOutputs); }
+
+    let mut spent = BTreeSet::new();
+    for (index, txin) in tx.inputs.iter().enumerate() {
+        if ! spent.insert((txin.id.as_ref().to_vec(), txin.index)) {
+            violations.push(TxViolation::DuplicateInput { index: index });
+        }
+    }
+
+    if txaux.witnesses.len() != tx.inputs.len() {
+        violations.push(TxViolation::WitnessCount { inputs: tx.inputs.len(), witnesses: txaux.witnesses.len() });
+    }
+    if resolved.len() != tx.inputs.len() {
+        violations.push(TxViolation::ResolvedCount { inputs: tx.inputs.len(), resolved: resolved.len() });
+    }
+
+    for (index, (witness, txout)) in txaux.witnesses.iter().zip(resolved.iter()).enumerate() {
+        match witness {
+            &tx::TxInWitness::PkWitness(ref xpub, _) => {
+                if ! is_address_of(xpub, &txout.address) {
+                    violations.push(TxViolation::WitnessAddressMismatch { index: index });
+                }
+            },
+            &tx::TxInWitness::RedeemWitness(ref key, _) => {
+                if redeem_address(key) != txout.address {
+                    violations.push(TxViolation::WitnessAddressMismatch { index: index });
+                }
+            },
+            &tx::TxInWitness::ScriptWitness(ref validator, ref redeemer) => {
+                if script_address(validator, txout.address.attributes.clone()) != txout.address {
+                    violations.push(TxViolation::WitnessAddressMismatch { index: index });
+                }
+                // only the multisig scripts are evaluated, there is no
+                // signature of the witness itself
+                if let Some(multisig) = Multisig::from_script(validator) {
+                    if ! multisig.verify(&cfg.config, tx, redeemer) {
+                        violations.push(TxViolation::ScriptRejected { index: index });
+                    }
+                }
+                continue;
+            },
+        }
+        if ! witness.verify_tx(&cfg.config, tx) {
+            violations.push(TxViolation::InvalidSignature { index: index });
+        }
+    }
+
+    // the redemption transactions (with redeem witnesses only) are exempt
+    // from fees
+    let redemption = txaux.witnesses.iter().all(|witness| match witness {
+        &tx::TxInWitness::RedeemWitness(..) => true,
+        _ => false
+    });
+    let fee = if redemption && ! txaux.witnesses.is_empty() {
+        0
+    } else {
+        u64::from(cfg.linear_fee().calculate_for_txaux(txaux)?.to_coin())
+    };
+    match (sum_values(resolved.iter()), sum_values(tx.outputs.iter())) {
+        (Some(inputs), Some(outputs)) => {
+            if outputs.checked_add(fee).map(|needed| inputs < needed).unwrap_or(true) {
+                violations.push(TxViolation::InsufficientFee {
+                    inputs: inputs.to_string(),
+                    outputs: outputs.to_string(),
+                    fee: fee.to_string()
+                });
+            }
+        },
+        _ => violations.push(TxViolation::ValueOverflow)
+    }
+
+    Ok(violations)
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct TxVerifyInput {
+    config: TxConfig,
+    /// the CBOR of the signed transaction (`TxAux`)
+    tx: Vec<u8>,
+    /// the outputs spent by the transaction, in the order of the inputs
+    resolved: Vec<tx::TxOut>
+}
+
+/// maximum number of inputs of a transaction skeleton, a transaction of
+/// that many inputs is already far larger than a block
+const MAX_SKELETON_INPUTS : u32 = 10_000;
+
+/// the transaction to estimate the fee of
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+enum FeeEstimationTx {
+    /// the CBOR of an unsigned transaction
+    Tx(Vec<u8>),
+    /// the number of inputs and the outputs of the transaction, the type
+    /// of the witnesses (a `PkWitness` for every input by default)
+    Skeleton {
+        inputs: u32,
+        outputs: Vec<tx::TxOut>,
+        #[serde(default)]
+        witnesses: Option<Vec<WitnessType>>
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct FeeEstimationInput {
+    #[serde(default)]
+    config: TxConfig,
+    tx: FeeEstimationTx
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct FeeEstimationOutput {
+    /// the fee (lovelace)
+    fee: u64,
+    /// the size of the signed transaction (bytes)
+    size: usize
+}
+
+fn estimate_tx_fee(input: FeeEstimationInput) -> Result<FeeEstimationOutput> {
+    let (tx, witnesses) = match input.tx {
+        FeeEstimationTx::Tx(bytes) => {
+            let tx : tx::Tx = decode_from_cbor(&bytes)?;
+            let witnesses = tx.inputs.iter().map(|_| WitnessType::PkWitness).collect();
+            (tx, witnesses)
+        },
+        FeeEstimationTx::Skeleton { inputs, outputs, witnesses } => {
+            // the largest possible inputs: the index is part of the CBOR
+            let txid = tx::TxId::from_slice(&[0; HASH_SIZE])?;
+            let mut tx = tx::Tx::new();
+            for _ in 0..inputs { tx.add_input(tx::TxIn::new(txid.clone(), u32::max_value())); }
+            for output in outputs { tx.add_output(output); }
+            let witnesses = witnesses.unwrap_or_else(|| (0..inputs).map(|_| WitnessType::PkWitness).collect());
+            (tx, witnesses)
+        }
+    };
+    if witnesses.len() != tx.inputs.len() {
+        return Err(Error::ErrorWitnessCount(tx.inputs.len(), witnesses.len()));
+    }
+    let size = signed_tx_size(&tx, &witnesses)?;
+    let fee = input.config.linear_fee().estimate(size)?;
+    Ok(FeeEstimationOutput { fee: u64::from(fee.to_coin()), size: size })
+}
+
+/// the fee and the size of the given transaction once signed, with the
+/// linear fee of the configuration
+#[no_mangle]
+pub extern "C" fn xtx_estimate_fee(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : FeeEstimationInput = input_json!(output_ptr, input_ptr, input_sz);
+    if let FeeEstimationTx::Skeleton { inputs, .. } = input.tx {
+        if inputs > MAX_SKELETON_INPUTS {
+            return raw_fail!(raw::INVALID_ARGUMENT, Some("inputs"), "at most {} inputs, not {}", MAX_SKELETON_INPUTS, inputs);
+        }
+    }
+    let output = jrpc_try!(output_ptr, estimate_tx_fee(input));
+    jrpc_ok!(output_ptr, output)
+}
+
+/// verify the signed transaction against the outputs it spends, returns
+/// the list of the violations (empty if the transaction is valid). The
+/// script witnesses are checked against the spent addresses, only the
+/// multisig scripts (see `Multisig`) are evaluated.
+#[no_mangle]
+pub extern "C" fn xtx_verify(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : TxVerifyInput = input_json!(output_ptr, input_ptr, input_sz);
+    let txaux : tx::TxAux = jrpc_try!(output_ptr, decode_from_cbor(&input.tx));
+    let violations = jrpc_try!(output_ptr, verify_txaux(&input.config, &txaux, &input.resolved));
+    jrpc_ok!(output_ptr, violations)
+}
+
+/// Registry of the objects kept on the rust side of the module.
+///
+/// Keys, wallets, accounts and transactions being built can be stored in
+/// the registry and referred to from javascript by an integer handle. This
+/// avoids serialising them back and forth at every call and keeps the
+/// private keys out of the javascript memory. Every object stored in the
+/// registry must be released with the matching `*_free` function.
+mod handle {
+    use std::cell::{RefCell};
+    use std::collections::{BTreeMap};
+    use std::i32;
+    use wallet_crypto::{hdwallet, tx};
+    use wallet_crypto::wallet::{Wallet, Account};
+    use super::{Error, Result};
+    use super::secret::{Secret};
+
+    pub enum Object {
+        XPrv(Secret<hdwallet::XPrv>),
+        XPub(hdwallet::XPub),
+        Wallet(Secret<Wallet>),
+        Account(Account),
+        Tx(tx::Tx),
+    }
+
+    /// type of the objects that can be stored in the registry
+    pub trait Handled: Sized {
+        fn into_object(self) -> Object;
+        fn from_object(object: Object) -> Option<Self>;
+        fn as_object(object: &mut Object) -> Option<&mut Self>;
+    }
+
+    macro_rules! handled {
+        ($constructor:ident, $ty:ty) => (
+            impl Handled for $ty {
+                fn into_object(self) -> Object { Object::$constructor(self) }
+                fn from_object(object: Object) -> Option<Self> {
+                    match object { Object::$constructor(v) => Some(v), _ => None }
+                }
+                fn as_object(object: &mut Object) -> Option<&mut Self> {
+                    match object { &mut Object::$constructor(ref mut v) => Some(v), _ => None }
+                }
+            }
+        );
+    }
+    handled!(XPrv, Secret<hdwallet::XPrv>);
+    handled!(XPub, hdwallet::XPub);
+    handled!(Wallet, Secret<Wallet>);
+    handled!(Account, Account);
+    handled!(Tx, tx::Tx);
+
+    struct Registry {
+        next: i32,
+        objects: BTreeMap<i32, Object>
+    }
+    impl Registry {
+        fn new() -> Self { Registry { next: 1, objects: BTreeMap::new() } }
+
+        fn insert(&mut self, object: Object) -> i32 {
+            // handles are strictly positive so they can't be mistaken
+            // for the negative status codes of the raw functions
+            while self.objects.contains_key(&self.next) {
+                self.next = if self.next == i32::MAX { 1 } else { self.next + 1 };
+            }
+            let handle = self.next;
+            self.objects.insert(handle, object);
+            self.next = if self.next == i32::MAX { 1 } else { self.next + 1 };
+            handle
+        }
+    }
+
+    thread_local!(static REGISTRY: RefCell<Registry> = RefCell::new(Registry::new()));
+
+    /// store the given object in the registry and return its handle
+    pub fn insert<T: Handled>(value: T) -> i32 {
+        REGISTRY.with(|registry| registry.borrow_mut().insert(value.into_object()))
+    }
+
+    /// run the given function on the object referred to by the handle.
+    ///
+    /// The registry is borrowed for the duration of the call, so the
+    /// function must not insert or remove objects itself.
+    pub fn with<T, F, R>(handle: i32, f: F) -> Result<R>
+        where T: Handled
+            , F: FnOnce(&mut T) -> R
+    {
+        REGISTRY.with(|registry| {
+            let mut registry = registry.borrow_mut();
+            match registry.objects.get_mut(&handle).and_then(T::as_object) {
+                None    => Err(Error::ErrorInvalidHandle(handle)),
+                Some(v) => Ok(f(v))
+            }
+        })
+    }
+
+    /// remove the object referred to by the handle from the registry
+    pub fn remove<T: Handled>(handle: i32) -> Result<T> {
+        REGISTRY.with(|registry| {
+            let mut registry = registry.borrow_mut();
+            let is_t = match registry.objects.get_mut(&handle) {
+                None => false,
+                Some(object) => T::as_object(object).is_some()
+            };
+            if ! is_t { return Err(Error::ErrorInvalidHandle(handle)); }
+            registry.objects.remove(&handle).and_then(T::from_object).ok_or(Error::ErrorInvalidHandle(handle))
+        })
+    }
+}
+
+/// free the object of the given type and handle
+macro_rules! handle_free {
+    ($ty:ty, $handle:ident) => ({
+        raw::call(|| {
+            let _ : $ty = raw_try!("handle", handle::remove($handle));
+            raw::SUCCESS
+        })
+    });
+}
+
+#[no_mangle]
+pub extern "C" fn hxprv_new(xprv_ptr: *const c_uchar) -> i32 {
+    raw::call(|| {
+        let xprv = raw_try!("xprv", unsafe { read_xprv(xprv_ptr) });
+        handle::insert(xprv)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn hxprv_from_seed(seed_ptr: *const c_uchar) -> i32 {
+    raw::call(|| {
+        let seed = raw_try!("seed", unsafe { read_seed(seed_ptr) });
+        handle::insert(secret::Secret::new(hdwallet::XPrv::generate_from_seed(&seed)))
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn hxprv_derive(xprv_handle: i32, scheme: u32, index: u32) -> i32 {
+    raw::call(|| {
+        let scheme = raw_try!("scheme", read_derivation_scheme(scheme));
+        let child = raw_try!("xprv", handle::with(xprv_handle, |xprv: &mut secret::Secret<hdwallet::XPrv>| {
+            secret::Secret::new(xprv.derive(scheme, index))
+        }));
+        handle::insert(child)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn hxprv_derive_path(xprv_handle: i32, scheme: u32, path_ptr: *const c_uchar, path_sz: usize) -> i32 {
+    raw::call(|| {
+        let scheme = raw_try!("scheme", read_derivation_scheme(scheme));
+        let path = raw_try!("path", unsafe { read_derivation_path(path_ptr, path_sz) });
+        let child = raw_try!("xprv", handle::with(xprv_handle, |xprv: &mut secret::Secret<hdwallet::XPrv>| {
+            derive_private_path(xprv, scheme, &path)
+        }));
+        handle::insert(child)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn hxprv_to_public(xprv_handle: i32) -> i32 {
+    raw::call(|| {
+        let xpub = raw_try!("xprv", handle::with(xprv_handle, |xprv: &mut secret::Secret<hdwallet::XPrv>| xprv.public()));
+        handle::insert(xpub)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn hxprv_sign(xprv_handle: i32, msg_ptr: *const c_uchar, msg_sz: usize, out: *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let msg = raw_try!("msg", unsafe { read_data(msg_ptr, msg_sz) });
+        let signature : hdwallet::Signature<Vec<u8>> =
+            raw_try!("xprv", handle::with(xprv_handle, |xprv: &mut secret::Secret<hdwallet::XPrv>| xprv.sign(&msg[..])));
+        raw_try!("out", unsafe { write_signature(&signature, out) });
+        raw::SUCCESS
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn hxprv_free(xprv_handle: i32) -> i32 {
+    handle_free!(secret::Secret<hdwallet::XPrv>, xprv_handle)
+}
+
+#[no_mangle]
+pub extern "C" fn hxpub_new(xpub_ptr: *const c_uchar) -> i32 {
+    raw::call(|| {
+        let xpub = raw_try!("xpub", unsafe { read_xpub(xpub_ptr) });
+        handle::insert(xpub)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn hxpub_export(xpub_handle: i32, out: *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let xpub = raw_try!("xpub", handle::with(xpub_handle, |xpub: &mut hdwallet::XPub| xpub.clone()));
+        raw_try!("out", unsafe { write_xpub(&xpub, out) });
+        raw::SUCCESS
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn hxpub_derive(xpub_handle: i32, scheme: u32, index: u32) -> i32 {
+    raw::call(|| {
+        let scheme = raw_try!("scheme", read_derivation_scheme(scheme));
+        let child = raw_try!("xpub", handle::with(xpub_handle, |xpub: &mut hdwallet::XPub| xpub.derive(scheme, index)));
+        let child = raw_try!("index", child);
+        handle::insert(child)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn hxpub_free(xpub_handle: i32) -> i32 {
+    handle_free!(hdwallet::XPub, xpub_handle)
+}
+
+#[no_mangle]
+pub extern "C" fn htx_new() -> i32 {
+    raw::call(|| {
+        handle::insert(tx::Tx::new())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn htx_add_input(tx_handle: i32, txid_ptr: *const c_uchar, index: u32) -> i32 {
+    raw::call(|| {
+        let txid_bytes = raw_try!("txid", unsafe { read_data(txid_ptr, HASH_SIZE) });
+        let txid = raw_try!("txid", tx::TxId::from_slice(&txid_bytes));
+        raw_try!("tx", handle::with(tx_handle, |tx: &mut tx::Tx| tx.add_input(tx::TxIn::new(txid, index))));
+        raw::SUCCESS
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn htx_add_output(tx_handle: i32, ea_ptr: *const c_uchar, ea_sz: usize, amount_lo: u32, amount_hi: u32) -> i32 {
+    raw::call(|| {
+        let ea_bytes = raw_try!("address", unsafe { read_data(ea_ptr, ea_sz) });
+        let ea = raw_try!("address", address::ExtendedAddr::from_bytes(&ea_bytes));
+        let coin = raw_try!("amount", read_amount(amount_lo, amount_hi));
+        raw_try!("tx", handle::with(tx_handle, |tx: &mut tx::Tx| tx.add_output(tx::TxOut::new(ea, coin))));
+        raw::SUCCESS
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn htx_to_cbor(tx_handle: i32, out: *mut *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let out_buf = raw_try!("tx", handle::with(tx_handle, |tx: &mut tx::Tx| encode_to_cbor(tx)));
+        let out_buf = raw_try!("tx", out_buf);
+        raw_try!("out", unsafe { return_data(out_buf, out) })
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn htx_sign(cfg_ptr: *const c_uchar, cfg_size: usize, xprv_handle: i32, tx_handle: i32, out: *mut c_uchar) -> i32 {
+    raw::call(|| {
+        let cfg = raw_try!("cfg", unsafe { read_config(cfg_ptr, cfg_size) });
+        let tx = raw_try!("tx", handle::with(tx_handle, |tx: &mut tx::Tx| tx.clone()));
+        let txinwitness = raw_try!("xprv", handle::with(xprv_handle, |xprv: &mut secret::Secret<hdwallet::XPrv>| {
+            tx::TxInWitness::new(&cfg, &xprv, &tx)
+        }));
+
+        let signature = match txinwitness {
+            tx::TxInWitness::PkWitness(_, sig) => sig,
+            // this should never happen as we are signing for the tx anyway
+            _ => return raw_fail!(raw::FAILED, None, "unexpected witness type")
+        };
+        raw_try!("out", unsafe { write_signature(&signature, out) });
+        raw::SUCCESS
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn htx_free(tx_handle: i32) -> i32 {
+    handle_free!(tx::Tx, tx_handle)
+}
+
+#[no_mangle]
+pub extern "C" fn hwallet_new(seed_ptr: *const c_uchar, scheme: u32) -> i32 {
+    raw::call(|| {
+        let seed = raw_try!("seed", unsafe { read_seed(seed_ptr) });
+        let scheme = raw_try!("scheme", read_derivation_scheme(scheme));
+        handle::insert(secret::Secret::new(Wallet::new_from_seed(&seed, scheme)))
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn hwallet_import(input_ptr: *const c_uchar, input_sz: usize) -> i32 {
+    raw::call(|| {
+        // the serialised wallet contains the root private key
+        let input = raw_try!("input", unsafe { read_secret(input_ptr, input_sz) });
+        let wallet : secret::Secret<Wallet> = raw_try!("input", serde_json::from_slice(&input));
+        handle::insert(wallet)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn hwallet_account(wallet_handle: i32, account: u32) -> i32 {
+    raw::call(|| {
+        let account = raw_try!("wallet", handle::with(wallet_handle, |wallet: &mut secret::Secret<Wallet>| wallet.account(account)));
+        handle::insert(account)
+    })
+}
+
+/// create a watch-only account from the account public key
+#[no_mangle]
+pub extern "C" fn haccount_from_xpub(xpub_ptr: *const c_uchar, scheme: u32) -> i32 {
+    raw::call(|| {
+        let xpub = raw_try!("xpub", unsafe { read_xpub(xpub_ptr) });
+        let scheme = raw_try!("scheme", read_derivation_scheme(scheme));
+        handle::insert(Account::new(xpub, scheme))
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn hwallet_free(wallet_handle: i32) -> i32 {
+    handle_free!(secret::Secret<Wallet>, wallet_handle)
+}
+
+#[no_mangle]
+pub extern "C" fn haccount_free(account_handle: i32) -> i32 {
+    handle_free!(Account, account_handle)
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct HWalletSpendInput {
+    wallet: i32,
+    inputs: tx::Inputs,
+    outputs: tx::Outputs,
+    change_addr: address::ExtendedAddr,
+    #[serde(default)]
+    selection_policy: Option<coin_selection::Policy>,
+    #[serde(default)]
+    linear_fee: Option<LinearFeeCoefficients>
+}
+
+/// same as `xwallet_spend` but with the wallet given by its handle
+#[no_mangle]
+pub extern "C" fn xhwallet_spend(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : HWalletSpendInput = input_json!(output_ptr, input_ptr, input_sz);
+    let output = jrpc_try!(output_ptr, handle::with(input.wallet, |wallet: &mut secret::Secret<Wallet>| {
+        wallet_spend(wallet, input.linear_fee, input.selection_policy.as_ref(), &input.inputs, &input.outputs, &input.change_addr)
+    }));
+    let output = jrpc_try!(output_ptr, output);
+    jrpc_ok!(output_ptr, output)
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct HWalletSweepInput {
+    wallet: i32,
+    inputs: tx::Inputs,
+    destination: address::ExtendedAddr,
+    /// the linear fee coefficients, the ones of the network by default
+    #[serde(default)]
+    linear_fee: Option<LinearFeeCoefficients>
+}
+
+/// same as `xwallet_sweep` but with the wallet given by its handle
+#[no_mangle]
+pub extern "C" fn xhwallet_sweep(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : HWalletSweepInput = input_json!(output_ptr, input_ptr, input_sz);
+    let output = jrpc_try!(output_ptr, handle::with(input.wallet, |wallet: &mut secret::Secret<Wallet>| {
+        wallet_sweep(wallet, input.linear_fee, &input.inputs, &input.destination)
+    }));
+    let output = jrpc_try!(output_ptr, output);
+    jrpc_ok!(output_ptr, output)
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct HGenAddressesInput {
+    account: i32,
+    derivation_scheme: hdwallet::DerivationScheme,
+    address_type: bip44::AddrType,
+    indices: Vec<u32>
+}
+
+/// same as `xwallet_addresses` but with the account given by its handle
+#[no_mangle]
+pub extern "C" fn xhaccount_addresses(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : HGenAddressesInput = input_json!(output_ptr, input_ptr, input_sz);
+    let addresses = jrpc_try!(output_ptr, handle::with(input.account, |account: &mut Account| {
+        check_derivation_scheme(account.derivation_scheme(), input.derivation_scheme)?;
+        Ok(account.gen_addresses(input.address_type, input.indices)?)
+    }));
+    let addresses : Vec<address::ExtendedAddr> = jrpc_try!(output_ptr, addresses);
+    jrpc_ok!(output_ptr, addresses)
+}
+
+/// the official BIP39 word lists
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+enum Language {
+    English,
+    French,
+    Japanese,
+    Korean,
+    ChineseSimplified,
+    ChineseTraditional,
+    Italian,
+    Spanish,
+}
+impl Default for Language {
+    fn default() -> Self { Language::English }
+}
+impl Language {
+    fn dictionary(self) -> &'static bip39::dictionary::DefaultDictionary {
+        match self {
+            Language::English            => &bip39::dictionary::ENGLISH,
+            Language::French             => &bip39::dictionary::FRENCH,
+            Language::Japanese           => &bip39::dictionary::JAPANESE,
+            Language::Korean             => &bip39::dictionary::KOREAN,
+            Language::ChineseSimplified  => &bip39::dictionary::CHINESE_SIMPLIFIED,
+            Language::ChineseTraditional => &bip39::dictionary::CHINESE_TRADITIONAL,
+            Language::Italian            => &bip39::dictionary::ITALIAN,
+            Language::Spanish            => &bip39::dictionary::SPANISH,
+        }
+    }
+}
+
+fn mnemonic_to_entropy(language: Language, mnemonic: &str) -> Result<secret::Secret<bip39::Entropy>> {
+    let mnemonics = bip39::Mnemonics::from_string(language.dictionary(), mnemonic)?;
+    Ok(secret::Secret::new(bip39::Entropy::from_mnemonics(&mnemonics)?))
+}
+
+/// the cardano wallet seed of the given entropy: the blake2b 256 hash of
+/// the entropy bytes (this is not the BIP39 seed).
+fn entropy_to_wallet_seed(entropy: &bip39::Entropy) -> Result<secret::Secret<hdwallet::Seed>> {
+    let mut b2b = Blake2b::new(hdwallet::SEED_SIZE);
+    let mut seed = secret::SecretBytes::new(repeat(0).take(hdwallet::SEED_SIZE).collect());
+    b2b.input(entropy.as_ref());
+    b2b.result(&mut seed);
+    Ok(secret::Secret::new(hdwallet::Seed::from_slice(&seed)?))
+}
+
+#[derive(Deserialize)]
+struct EntropyToMnemonicInput {
+    entropy: Vec<u8>,
+    #[serde(default)]
+    language: Language
+}
+
+/// the mnemonic phrase (a string) of the given entropy (16, 20, 24, 28 or
+/// 32 bytes)
+#[no_mangle]
+pub extern "C" fn xbip39_entropy_to_mnemonic(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : EntropyToMnemonicInput = input_secret_json!(output_ptr, input_ptr, input_sz);
+    let input_entropy = secret::SecretBytes::new(input.entropy);
+    let entropy = secret::Secret::new(jrpc_try!(output_ptr, bip39::Entropy::from_slice(&input_entropy)));
+    let mnemonics = entropy.to_mnemonics();
+    let mnemonic = secret::SecretString::new(mnemonics.to_string(input.language.dictionary()).to_string());
+    jrpc_ok_secret!(output_ptr, &*mnemonic)
+}
+
+#[derive(Deserialize)]
+struct MnemonicInput {
+    mnemonic: secret::SecretString,
+    #[serde(default)]
+    language: Language
+}
+
+/// check the words and the checksum of the given mnemonic phrase, returns
+/// true or the reason why the mnemonic phrase is invalid
+#[no_mangle]
+pub extern "C" fn xbip39_mnemonic_check(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : MnemonicInput = input_secret_json!(output_ptr, input_ptr, input_sz);
+    let _ = jrpc_try!(output_ptr, mnemonic_to_entropy(input.language, &input.mnemonic));
+    jrpc_ok!(output_ptr, true)
+}
+
+/// the entropy (array of bytes) of the given mnemonic phrase
+#[no_mangle]
+pub extern "C" fn xbip39_mnemonic_to_entropy(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : MnemonicInput = input_secret_json!(output_ptr, input_ptr, input_sz);
+    let entropy = jrpc_try!(output_ptr, mnemonic_to_entropy(input.language, &input.mnemonic));
+    jrpc_ok_secret!(output_ptr, entropy.as_ref())
+}
+
+#[derive(Deserialize)]
+struct MnemonicToSeedInput {
+    mnemonic: secret::SecretString,
+    #[serde(default)]
+    password: secret::SecretString,
+    #[serde(default)]
+    language: Language
+}
+
+/// the standard BIP39 seed (64 bytes) of the given mnemonic phrase and
+/// password
+#[no_mangle]
+pub extern "C" fn xbip39_mnemonic_to_seed(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : MnemonicToSeedInput = input_secret_json!(output_ptr, input_ptr, input_sz);
+    let mnemonic = jrpc_try!(output_ptr, bip39::MnemonicString::new(input.language.dictionary(), input.mnemonic.to_string()));
+    let seed = secret::Secret::new(bip39::Seed::from_mnemonic_string(&mnemonic, input.password.as_bytes()));
+    jrpc_ok_secret!(output_ptr, seed.as_ref())
+}
+
+/// the cardano wallet seed (32 bytes, as expected by `xwallet_create`) of
+/// the given mnemonic phrase
+#[no_mangle]
+pub extern "C" fn xbip39_mnemonic_to_wallet_seed(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : MnemonicInput = input_secret_json!(output_ptr, input_ptr, input_sz);
+    let entropy = jrpc_try!(output_ptr, mnemonic_to_entropy(input.language, &input.mnemonic));
+    let seed = jrpc_try!(output_ptr, entropy_to_wallet_seed(&entropy));
+    jrpc_ok_secret!(output_ptr, seed.as_ref())
+}
+
+#[derive(Deserialize)]
+struct WalletFromMnemonicInput {
+    mnemonic: secret::SecretString,
+    #[serde(default)]
+    language: Language,
+    derivation_scheme: hdwallet::DerivationScheme
+}
+
+/// create the wallet of the given mnemonic phrase
+#[no_mangle]
+pub extern "C" fn xwallet_from_mnemonic(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : WalletFromMnemonicInput = input_secret_json!(output_ptr, input_ptr, input_sz);
+    let entropy = jrpc_try!(output_ptr, mnemonic_to_entropy(input.language, &input.mnemonic));
+    let seed = jrpc_try!(output_ptr, entropy_to_wallet_seed(&entropy));
+    let wallet = secret::Secret::new(Wallet::new_from_seed(&seed, input.derivation_scheme));
+    jrpc_ok_secret!(output_ptr, &*wallet)
+}
+
+/// CBOR encoding of the given bytes (major type 2), as used to build the
+/// legacy Daedalus seed
+fn cbor_bytes(bytes: &[u8]) -> secret::SecretBytes {
+    let mut out = Vec::with_capacity(bytes.len() + 3);
+    if bytes.len() < 24 {
+        out.push(0x40 | bytes.len() as u8);
+    } else if bytes.len() < 0x100 {
+        out.push(0x58);
+        out.push(bytes.len() as u8);
+    } else {
+        out.push(0x59);
+        out.push((bytes.len() >> 8) as u8);
+        out.push(bytes.len() as u8);
+    }
+    out.extend_from_slice(bytes);
+    secret::SecretBytes::new(out)
+}
+
+/// the Daedalus wallets are created from 12 words (16 bytes of entropy)
+const DAEDALUS_ENTROPY_SIZE : usize = 16;
+
+/// the entropy of the given Daedalus mnemonic phrase, which must have 12 words
+fn daedalus_entropy(language: Language, mnemonic: &str) -> Result<secret::Secret<bip39::Entropy>> {
+    let entropy = mnemonic_to_entropy(language, mnemonic)?;
+    if entropy.as_ref().len() != DAEDALUS_ENTROPY_SIZE {
+        // 3 words for every 4 bytes of entropy
+        let words = entropy.as_ref().len() * 3 / 4;
+        return Err(Error::ErrorDaedalusMnemonic(format!("a Daedalus mnemonic phrase has 12 words, not {}", words)));
+    }
+    Ok(entropy)
+}
+
+/// the root key of a legacy Daedalus wallet: the seed is the blake2b 256
+/// hash of the CBOR encoded entropy, given CBOR encoded to the legacy key
+/// generation.
+fn daedalus_root_key(entropy: &bip39::Entropy) -> secret::Secret<hdwallet::XPrv> {
+    let mut b2b = Blake2b::new(hdwallet::SEED_SIZE);
+    let mut seed = secret::SecretBytes::new(repeat(0).take(hdwallet::SEED_SIZE).collect());
+    b2b.input(&cbor_bytes(entropy.as_ref()));
+    b2b.result(&mut seed);
+    secret::Secret::new(hdwallet::XPrv::generate_from_daedalus_seed(&cbor_bytes(&seed)))
+}
+
+/// the location of an address of a Daedalus wallet
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+struct DaedalusPath {
+    account: u32,
+    index: u32
+}
+
+/// decrypt the derivation path stored in the address and check the address
+/// is the one of the wallet at this path (the payload alone could be
+/// copied from another address)
+fn daedalus_address_path(root: &hdwallet::XPrv, hdkey: &hdpayload::HDKey, addr: &address::ExtendedAddr) -> Option<DaedalusPath> {
+    let payload = match addr.attributes.derivation_path {
+        None => return None,
+        Some(ref payload) => payload
+    };
+    let path = hdkey.decrypt_path(payload)?;
+    // the Daedalus addresses are at `account'/index'` from the root key
+    if path.as_ref().len() != 2 { return None; }
+    let path = DaedalusPath { account: path.as_ref()[0], index: path.as_ref()[1] };
+    let scheme = hdwallet::DerivationScheme::V1;
+    let xprv = derive_private_path(root, scheme, &[path.account, path.index]);
+    if is_address_of(&xprv.public(), addr) { Some(path) } else { None }
+}
+
+#[derive(Deserialize)]
+struct DaedalusRestoreInput {
+    mnemonic: secret::SecretString,
+    #[serde(default)]
+    language: Language,
+    #[serde(default)]
+    addresses: Vec<address::ExtendedAddr>,
+    #[serde(default)]
+    outputs: Vec<tx::TxOut>
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct DaedalusAddress {
+    address: address::ExtendedAddr,
+    path: DaedalusPath
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct DaedalusOutput {
+    output: tx::TxOut,
+    path: DaedalusPath
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct DaedalusRestoreOutput {
+    addresses: Vec<DaedalusAddress>,
+    outputs: Vec<DaedalusOutput>
+}
+
+/// recognise, among the given addresses and unspent outputs, the ones of
+/// the legacy Daedalus wallet of the given mnemonic phrase (12 words) and
+/// returns them with their `(account, index)` derivation path
+#[no_mangle]
+pub extern "C" fn xwallet_daedalus_restore(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : DaedalusRestoreInput = input_secret_json!(output_ptr, input_ptr, input_sz);
+    let entropy = jrpc_try!(output_ptr, daedalus_entropy(input.language, &input.mnemonic));
+    let root = daedalus_root_key(&entropy);
+    let hdkey = hdpayload::HDKey::new(&root.public());
+
+    let addresses = input.addresses.into_iter().filter_map(|address| {
+        daedalus_address_path(&root, &hdkey, &address).map(|path| DaedalusAddress { address: address, path: path })
+    }).collect();
+    let outputs = input.outputs.into_iter().filter_map(|output| {
+        daedalus_address_path(&root, &hdkey, &output.address).map(|path| DaedalusOutput { output: output, path: path })
+    }).collect();
+    jrpc_ok!(output_ptr, DaedalusRestoreOutput { addresses: addresses, outputs: outputs })
+}
+
+/// the root private key of the legacy Daedalus wallet of the given mnemonic
+/// phrase (12 words): the keys of the restored addresses are derived from it
+/// with the `V1` scheme at `account'/index'`
+#[no_mangle]
+pub extern "C" fn xwallet_daedalus_root_key(input_ptr: *const c_uchar, input_sz: usize, output_ptr: *mut *mut c_uchar) -> i32 {
+    let input : MnemonicInput = input_secret_json!(output_ptr, input_ptr, input_sz);
+    let entropy = jrpc_try!(output_ptr, daedalus_entropy(input.language, &input.mnemonic));
+    let root = daedalus_root_key(&entropy);
+    jrpc_ok_secret!(output_ptr, root.as_ref())
+}